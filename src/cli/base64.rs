@@ -1,10 +1,15 @@
 use std::fmt;
+use std::io::Write;
+use std::path::PathBuf;
 use std::str::FromStr;
 
 use clap::Parser;
 use enum_dispatch::enum_dispatch;
 
-use crate::{process_decode, process_encode, CmdExecutor};
+use crate::utils::get_writer;
+use crate::{
+    process_armor, process_data_uri, process_dearmor, process_decode, process_encode, CmdExecutor,
+};
 
 use super::verify_file;
 
@@ -15,6 +20,12 @@ pub enum Base64SubCommand {
     Encode(Base64EncodeOpts),
     #[command(name = "decode", about = "Decode a base64 string")]
     Decode(Base64DecodeOpts),
+    #[command(name = "data-uri", about = "Encode a file as a data: URI")]
+    DataUri(Base64DataUriOpts),
+    #[command(name = "armor", about = "Wrap data in a PEM style armored block")]
+    Armor(Base64ArmorOpts),
+    #[command(name = "dearmor", about = "Decode an armored block")]
+    Dearmor(Base64DearmorOpts),
 }
 
 #[derive(Debug, Parser)]
@@ -23,6 +34,13 @@ pub struct Base64EncodeOpts {
     pub input: String,
     #[arg(long, value_parser = parse_base64_format, default_value = "standard")]
     pub format: Base64Format,
+    #[arg(
+        long,
+        help = "Break the output into lines of N characters, mime defaults to 76"
+    )]
+    pub wrap: Option<usize>,
+    #[arg(short, long, help = "Write to a file instead of stdout")]
+    pub output: Option<PathBuf>,
 }
 
 #[derive(Debug, Parser)]
@@ -31,27 +49,112 @@ pub struct Base64DecodeOpts {
     pub input: String,
     #[arg(long, value_parser = parse_base64_format, default_value = "standard")]
     pub format: Base64Format,
+    #[arg(
+        long,
+        help = "Accept either padding style and, for the standard and urlsafe formats, either alphabet"
+    )]
+    pub lenient: bool,
+    #[arg(
+        short,
+        long,
+        help = "Write the decoded bytes to a file instead of stdout"
+    )]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Parser)]
+pub struct Base64DataUriOpts {
+    #[arg(value_parser = verify_file, default_value = "-")]
+    pub input: String,
+    #[arg(long, help = "MIME type, detected from the content by default")]
+    pub mime: Option<String>,
+    #[arg(short, long, help = "Write to a file instead of stdout")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Parser)]
+pub struct Base64ArmorOpts {
+    #[arg(short, long, value_parser = verify_file, default_value = "-")]
+    pub input: String,
+    #[arg(long, default_value = "RCLI MESSAGE")]
+    pub label: String,
+    #[arg(short, long, help = "Write to a file instead of stdout")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Parser)]
+pub struct Base64DearmorOpts {
+    #[arg(short, long, value_parser = verify_file, default_value = "-")]
+    pub input: String,
+    #[arg(long, help = "Only accept a block with this label")]
+    pub label: Option<String>,
+    #[arg(
+        short,
+        long,
+        help = "Write the decoded bytes to a file instead of stdout"
+    )]
+    pub output: Option<PathBuf>,
 }
 
 #[derive(Debug, Copy, Clone)]
 pub enum Base64Format {
     Standard,
+    StandardNoPad,
     UrlSafe,
+    UrlSafePad,
+    Mime,
+    Bcrypt,
+    Imap,
 }
 
 impl CmdExecutor for Base64EncodeOpts {
     async fn execute(self) -> anyhow::Result<()> {
-        let encoded = process_encode(&self.input, self.format)?;
-        println!("{}", encoded);
+        let mut writer = get_writer(self.output.as_deref())?;
+        process_encode(&self.input, &mut writer, self.format, self.wrap)?;
+        if self.output.is_none() {
+            writeln!(writer)?;
+        }
+        writer.flush()?;
         Ok(())
     }
 }
 
 impl CmdExecutor for Base64DecodeOpts {
     async fn execute(self) -> anyhow::Result<()> {
-        let decoded = process_decode(&self.input, self.format)?;
-        let decoded = String::from_utf8(decoded)?;
-        println!("{}", decoded);
+        // raw bytes, no utf-8 conversion or trailing newline so binaries round-trip
+        let mut writer = get_writer(self.output.as_deref())?;
+        process_decode(&self.input, &mut writer, self.format, self.lenient)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+impl CmdExecutor for Base64DataUriOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let mut writer = get_writer(self.output.as_deref())?;
+        process_data_uri(&self.input, &mut writer, self.mime.as_deref())?;
+        if self.output.is_none() {
+            writeln!(writer)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+impl CmdExecutor for Base64ArmorOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let mut writer = get_writer(self.output.as_deref())?;
+        process_armor(&self.input, &mut writer, &self.label)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+impl CmdExecutor for Base64DearmorOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let mut writer = get_writer(self.output.as_deref())?;
+        process_dearmor(&self.input, &mut writer, self.label.as_deref())?;
+        writer.flush()?;
         Ok(())
     }
 }
@@ -66,7 +169,12 @@ impl FromStr for Base64Format {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "standard" => Ok(Base64Format::Standard),
+            "standard-nopad" => Ok(Base64Format::StandardNoPad),
             "urlsafe" => Ok(Base64Format::UrlSafe),
+            "urlsafe-pad" => Ok(Base64Format::UrlSafePad),
+            "mime" => Ok(Base64Format::Mime),
+            "bcrypt" => Ok(Base64Format::Bcrypt),
+            "imap" => Ok(Base64Format::Imap),
             _ => anyhow::bail!("Unsupported base64 format"),
         }
     }
@@ -76,7 +184,12 @@ impl From<Base64Format> for &'static str {
     fn from(format: Base64Format) -> &'static str {
         match format {
             Base64Format::Standard => "standard",
+            Base64Format::StandardNoPad => "standard-nopad",
             Base64Format::UrlSafe => "urlsafe",
+            Base64Format::UrlSafePad => "urlsafe-pad",
+            Base64Format::Mime => "mime",
+            Base64Format::Bcrypt => "bcrypt",
+            Base64Format::Imap => "imap",
         }
     }
 }
@@ -0,0 +1,122 @@
+use std::fmt;
+use std::io::Write;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use clap::Parser;
+
+use crate::utils::get_writer;
+use crate::{process_codec_decode, process_codec_encode, CmdExecutor};
+
+use super::verify_file;
+
+#[derive(Debug, Copy, Clone)]
+pub enum Codec {
+    Hex,
+    Base32,
+    Base32Hex,
+    Base58,
+    Base85,
+    Z85,
+}
+
+#[derive(Debug, Parser)]
+pub struct EncodeOpts {
+    #[arg(short, long, value_parser = verify_file, default_value = "-")]
+    pub input: String,
+    #[arg(long, value_parser = parse_codec, default_value = "hex")]
+    pub codec: Codec,
+    #[arg(short, long, help = "Write to a file instead of stdout")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Parser)]
+pub struct DecodeOpts {
+    #[arg(short, long, value_parser = verify_file, default_value = "-")]
+    pub input: String,
+    #[arg(long, value_parser = parse_codec, default_value = "hex")]
+    pub codec: Codec,
+    #[arg(
+        short,
+        long,
+        help = "Write the decoded bytes to a file instead of stdout"
+    )]
+    pub output: Option<PathBuf>,
+}
+
+impl CmdExecutor for EncodeOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let mut writer = get_writer(self.output.as_deref())?;
+        process_codec_encode(&self.input, &mut writer, self.codec)?;
+        if self.output.is_none() {
+            writeln!(writer)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+impl CmdExecutor for DecodeOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let Some(output) = self.output else {
+            let mut writer = get_writer(None)?;
+            process_codec_decode(&self.input, &mut writer, self.codec)?;
+            writer.flush()?;
+            return Ok(());
+        };
+        // decode next to the output and rename it on success, so bad input never leaves
+        // a partly decoded file behind
+        let mut tmp = output.clone().into_os_string();
+        tmp.push(".tmp");
+        let tmp = PathBuf::from(tmp);
+        let result = get_writer(Some(&tmp)).and_then(|mut writer| {
+            process_codec_decode(&self.input, &mut writer, self.codec)?;
+            Ok(writer.flush()?)
+        });
+        if let Err(e) = result {
+            let _ = std::fs::remove_file(&tmp);
+            return Err(e);
+        }
+        std::fs::rename(tmp, output)?;
+        Ok(())
+    }
+}
+
+fn parse_codec(codec: &str) -> anyhow::Result<Codec, anyhow::Error> {
+    codec.parse()
+}
+
+impl FromStr for Codec {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hex" => Ok(Codec::Hex),
+            "base32" => Ok(Codec::Base32),
+            "base32hex" => Ok(Codec::Base32Hex),
+            "base58" => Ok(Codec::Base58),
+            "base85" => Ok(Codec::Base85),
+            "z85" => Ok(Codec::Z85),
+            _ => anyhow::bail!("Unsupported codec"),
+        }
+    }
+}
+
+impl From<Codec> for &'static str {
+    fn from(codec: Codec) -> &'static str {
+        match codec {
+            Codec::Hex => "hex",
+            Codec::Base32 => "base32",
+            Codec::Base32Hex => "base32hex",
+            Codec::Base58 => "base58",
+            Codec::Base85 => "base85",
+            Codec::Z85 => "z85",
+        }
+    }
+}
+
+impl fmt::Display for Codec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", Into::<&str>::into(*self))
+    }
+}
@@ -39,7 +39,7 @@ impl CmdExecutor for CsvOpts {
     }
 }
 
-fn parse_format(format: &str) -> anyhow::Result<OutputFormat, anyhow::Error> {
+pub(super) fn parse_format(format: &str) -> anyhow::Result<OutputFormat, anyhow::Error> {
     format.parse()
 }
 
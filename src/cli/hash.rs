@@ -0,0 +1,113 @@
+use std::fmt;
+use std::str::FromStr;
+
+use clap::Parser;
+
+use crate::{process_hash, process_hash_check, CmdExecutor};
+
+use super::verify_file;
+
+#[derive(Debug, Copy, Clone)]
+pub enum HashAlgorithm {
+    Blake3,
+    Sha256,
+    Sha512,
+    Sha3_256,
+    Sha3_512,
+    Xxh64,
+    Xxh3,
+}
+
+#[derive(Debug, Parser)]
+pub struct HashOpts {
+    #[arg(value_parser = verify_file, default_value = "-", help = "Files or directories to hash, - for stdin")]
+    pub inputs: Vec<String>,
+    #[arg(short, long, value_parser = parse_algorithm, default_value = "sha256")]
+    pub algorithm: HashAlgorithm,
+    #[arg(short, long, help = "Hash every file under the given directories")]
+    pub recursive: bool,
+    #[arg(
+        short,
+        long,
+        conflicts_with = "recursive",
+        help = "Read checksum lists from the inputs and verify them"
+    )]
+    pub check: bool,
+    #[arg(long, requires = "check", help = "Only print failed files")]
+    pub quiet: bool,
+}
+
+impl CmdExecutor for HashOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        if !self.check {
+            let entries = process_hash(&self.inputs, self.algorithm, self.recursive)?;
+            for entry in entries {
+                println!("{}", entry);
+            }
+            return Ok(());
+        }
+        let mut failed = 0;
+        for input in &self.inputs {
+            let report = process_hash_check(input, self.algorithm)?;
+            if report.malformed > 0 {
+                eprintln!(
+                    "{}: WARNING: {} line(s) are improperly formatted",
+                    input, report.malformed
+                );
+            }
+            for result in report.results {
+                if !result.is_ok() {
+                    failed += 1;
+                }
+                if !self.quiet || !result.is_ok() {
+                    println!("{}", result);
+                }
+            }
+        }
+        if failed > 0 {
+            anyhow::bail!("{} computed checksum(s) did NOT match", failed);
+        }
+        Ok(())
+    }
+}
+
+fn parse_algorithm(algorithm: &str) -> anyhow::Result<HashAlgorithm, anyhow::Error> {
+    algorithm.parse()
+}
+
+impl From<HashAlgorithm> for &'static str {
+    fn from(algorithm: HashAlgorithm) -> &'static str {
+        match algorithm {
+            HashAlgorithm::Blake3 => "blake3",
+            HashAlgorithm::Sha256 => "sha256",
+            HashAlgorithm::Sha512 => "sha512",
+            HashAlgorithm::Sha3_256 => "sha3-256",
+            HashAlgorithm::Sha3_512 => "sha3-512",
+            HashAlgorithm::Xxh64 => "xxh64",
+            HashAlgorithm::Xxh3 => "xxh3",
+        }
+    }
+}
+
+impl FromStr for HashAlgorithm {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "blake3" => Ok(HashAlgorithm::Blake3),
+            "sha256" => Ok(HashAlgorithm::Sha256),
+            "sha512" => Ok(HashAlgorithm::Sha512),
+            "sha3-256" => Ok(HashAlgorithm::Sha3_256),
+            "sha3-512" => Ok(HashAlgorithm::Sha3_512),
+            "xxh64" => Ok(HashAlgorithm::Xxh64),
+            "xxh3" => Ok(HashAlgorithm::Xxh3),
+            _ => anyhow::bail!("Unsupported hash algorithm"),
+        }
+    }
+}
+
+impl fmt::Display for HashAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", Into::<&str>::into(*self))
+    }
+}
@@ -0,0 +1,104 @@
+use std::fmt;
+use std::io::{IsTerminal, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use clap::Parser;
+
+use crate::utils::get_writer;
+use crate::{process_hexdump, process_hexdump_reverse, CmdExecutor};
+
+use super::verify_file;
+
+#[derive(Debug, Copy, Clone)]
+pub enum ColorMode {
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Debug, Parser)]
+pub struct HexdumpOpts {
+    #[arg(short, long, value_parser = verify_file, default_value = "-")]
+    pub input: String,
+    #[arg(short, long, default_value_t = 0, help = "Start at this byte offset")]
+    pub skip: u64,
+    #[arg(short = 'n', long, help = "Stop after this many bytes")]
+    pub length: Option<u64>,
+    #[arg(
+        short,
+        long,
+        default_value_t = 2,
+        help = "Bytes per group, 0 for no spaces"
+    )]
+    pub group: usize,
+    #[arg(long, value_parser = parse_color_mode, default_value = "auto")]
+    pub color: ColorMode,
+    #[arg(
+        short,
+        long,
+        conflicts_with_all = ["skip", "length"],
+        help = "Turn a dump back into binary"
+    )]
+    pub reverse: bool,
+    #[arg(short, long, help = "Write to a file instead of stdout")]
+    pub output: Option<PathBuf>,
+}
+
+impl CmdExecutor for HexdumpOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let mut writer = get_writer(self.output.as_deref())?;
+        if self.reverse {
+            process_hexdump_reverse(&self.input, &mut writer)?;
+        } else {
+            let color = match self.color {
+                ColorMode::Always => true,
+                ColorMode::Never => false,
+                ColorMode::Auto => self.output.is_none() && std::io::stdout().is_terminal(),
+            };
+            process_hexdump(
+                &self.input,
+                &mut writer,
+                self.skip,
+                self.length,
+                self.group,
+                color,
+            )?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+fn parse_color_mode(mode: &str) -> anyhow::Result<ColorMode, anyhow::Error> {
+    mode.parse()
+}
+
+impl FromStr for ColorMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(ColorMode::Auto),
+            "always" => Ok(ColorMode::Always),
+            "never" => Ok(ColorMode::Never),
+            _ => anyhow::bail!("Unsupported color mode"),
+        }
+    }
+}
+
+impl From<ColorMode> for &'static str {
+    fn from(mode: ColorMode) -> &'static str {
+        match mode {
+            ColorMode::Auto => "auto",
+            ColorMode::Always => "always",
+            ColorMode::Never => "never",
+        }
+    }
+}
+
+impl fmt::Display for ColorMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", Into::<&str>::into(*self))
+    }
+}
@@ -0,0 +1,121 @@
+use std::fmt;
+use std::io::Write;
+use std::str::FromStr;
+
+use clap::Parser;
+use enum_dispatch::enum_dispatch;
+
+use crate::utils::get_writer;
+use crate::{process_id_generate, process_id_parse, CmdExecutor};
+
+#[derive(Debug, Parser)]
+#[enum_dispatch(CmdExecutor)]
+pub enum IdSubCommand {
+    #[command(
+        name = "generate",
+        about = "Generate UUID, ULID, nanoid or snowflake ids"
+    )]
+    Generate(IdGenerateOpts),
+    #[command(
+        name = "parse",
+        about = "Show the kind and embedded timestamp of an id"
+    )]
+    Parse(IdParseOpts),
+}
+
+#[derive(Debug, Copy, Clone)]
+pub enum IdKind {
+    UuidV4,
+    UuidV7,
+    Ulid,
+    NanoId,
+    Snowflake,
+}
+
+#[derive(Debug, Parser)]
+pub struct IdGenerateOpts {
+    #[arg(long, value_parser = parse_id_kind, default_value = "uuid-v4")]
+    pub kind: IdKind,
+    #[arg(
+        short,
+        long,
+        value_parser = clap::value_parser!(u32).range(1..=1_000_000),
+        default_value_t = 1
+    )]
+    pub count: u32,
+    #[arg(
+        short,
+        long,
+        value_parser = clap::value_parser!(u16).range(1..=256),
+        default_value_t = 21,
+        help = "Length of nanoids"
+    )]
+    pub length: u16,
+    #[arg(long, default_value_t = 0, help = "Node id of snowflakes, 0-1023")]
+    pub node: u16,
+}
+
+#[derive(Debug, Parser)]
+pub struct IdParseOpts {
+    pub id: String,
+}
+
+impl CmdExecutor for IdGenerateOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let mut writer = get_writer(None)?;
+        process_id_generate(
+            self.kind,
+            self.count as usize,
+            self.length as usize,
+            self.node,
+            &mut writer,
+        )?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+impl CmdExecutor for IdParseOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let info = process_id_parse(&self.id)?;
+        println!("{}", info);
+        Ok(())
+    }
+}
+
+fn parse_id_kind(kind: &str) -> anyhow::Result<IdKind, anyhow::Error> {
+    kind.parse()
+}
+
+impl FromStr for IdKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "uuid-v4" => Ok(IdKind::UuidV4),
+            "uuid-v7" => Ok(IdKind::UuidV7),
+            "ulid" => Ok(IdKind::Ulid),
+            "nanoid" => Ok(IdKind::NanoId),
+            "snowflake" => Ok(IdKind::Snowflake),
+            _ => anyhow::bail!("Unsupported id kind"),
+        }
+    }
+}
+
+impl From<IdKind> for &'static str {
+    fn from(kind: IdKind) -> &'static str {
+        match kind {
+            IdKind::UuidV4 => "uuid-v4",
+            IdKind::UuidV7 => "uuid-v7",
+            IdKind::Ulid => "ulid",
+            IdKind::NanoId => "nanoid",
+            IdKind::Snowflake => "snowflake",
+        }
+    }
+}
+
+impl fmt::Display for IdKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", Into::<&str>::into(*self))
+    }
+}
@@ -0,0 +1,23 @@
+use clap::Parser;
+
+use crate::{process_inspect, CmdExecutor};
+
+#[derive(Debug, Parser)]
+pub struct InspectOpts {
+    #[arg(help = "The string to inspect, or a file (- for stdin)")]
+    pub input: String,
+    #[arg(
+        long,
+        default_value_t = 8,
+        help = "Stop after decoding this many layers"
+    )]
+    pub max_depth: usize,
+}
+
+impl CmdExecutor for InspectOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let inspection = process_inspect(&self.input, self.max_depth)?;
+        println!("{}", inspection);
+        Ok(())
+    }
+}
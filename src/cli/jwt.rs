@@ -1,7 +1,9 @@
 use clap::Parser;
 use enum_dispatch::enum_dispatch;
 
-use crate::{process_jwt_sign, process_jwt_verify, CmdExecutor};
+use crate::{process_jwt_sign, process_jwt_verify, resolve_vault_ref, CmdExecutor};
+
+use super::report_verify;
 
 #[derive(Debug, Parser)]
 #[enum_dispatch(CmdExecutor)]
@@ -20,7 +22,11 @@ pub struct JwtSignOpts {
     pub aud: String,
     #[arg(long, value_parser = parse_exp, default_value = "1d")]
     pub exp: usize,
-    #[arg(long, default_value = "thisisasecret")]
+    #[arg(
+        long,
+        default_value = "thisisasecret",
+        help = "Secret or vault:name reference"
+    )]
     pub secret: String,
 }
 
@@ -28,13 +34,20 @@ pub struct JwtSignOpts {
 pub struct JwtVerifyOpts {
     #[arg(short, long)]
     pub token: String,
-    #[arg(long, default_value = "thisisasecret")]
+    #[arg(
+        long,
+        default_value = "thisisasecret",
+        help = "Secret or vault:name reference"
+    )]
     pub secret: String,
+    #[arg(long, help = "Print the result as JSON")]
+    pub json: bool,
 }
 
 impl CmdExecutor for JwtSignOpts {
     async fn execute(self) -> anyhow::Result<()> {
-        let token = process_jwt_sign(&self.sub, &self.aud, self.exp, &self.secret)?;
+        let secret = resolve_secret(&self.secret)?;
+        let token = process_jwt_sign(&self.sub, &self.aud, self.exp, &secret)?;
         println!("{}", token);
         Ok(())
     }
@@ -42,9 +55,16 @@ impl CmdExecutor for JwtSignOpts {
 
 impl CmdExecutor for JwtVerifyOpts {
     async fn execute(self) -> anyhow::Result<()> {
-        let verified = process_jwt_verify(&self.token, &self.secret);
-        println!("{}", verified);
-        Ok(())
+        let result = resolve_secret(&self.secret)
+            .and_then(|secret| process_jwt_verify(&self.token, &secret));
+        report_verify(result, "HS256", self.json)
+    }
+}
+
+fn resolve_secret(secret: &str) -> anyhow::Result<String> {
+    match resolve_vault_ref(secret)? {
+        Some(secret) => Ok(String::from_utf8(secret)?),
+        None => Ok(secret.to_string()),
     }
 }
 
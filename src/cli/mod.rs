@@ -3,20 +3,39 @@ use std::path::{Path, PathBuf};
 use clap::Parser;
 use enum_dispatch::enum_dispatch;
 
-pub use self::{base64::*, csv::*, genpass::*, http::*, jwt::*, text::*};
+use crate::{CmdExit, VerifyResult, VAULT_REF_PREFIX};
+
+pub use self::{
+    base64::*, codec::*, csv::*, genpass::*, hash::*, hexdump::*, http::*, id::*, inspect::*,
+    jwt::*, qr::*, text::*, url::*, vault::*,
+};
 
 mod base64;
+mod codec;
 mod csv;
 mod genpass;
+mod hash;
+mod hexdump;
 mod http;
+mod id;
+mod inspect;
 mod jwt;
+mod qr;
 mod text;
+mod url;
+mod vault;
 
 #[derive(Debug, Parser)]
 #[command(name = "rcli", version, author, about, long_about = None)]
 pub struct Opts {
     #[command(subcommand)]
     pub cmd: SubCommand,
+    #[arg(
+        long,
+        global = true,
+        help = "Vault file for vault commands and vault:name keys, defaults to RCLI_VAULT or ~/.rcli.vault"
+    )]
+    pub vault: Option<PathBuf>,
 }
 
 #[derive(Debug, Parser)]
@@ -28,23 +47,80 @@ pub enum SubCommand {
     GenPass(GenPassOpts),
     #[command(subcommand, about = "Base64 encode/decode")]
     Base64(Base64SubCommand),
+    #[command(
+        name = "encode",
+        about = "Encode binary data as hex, base32, base58 or base85"
+    )]
+    Encode(EncodeOpts),
+    #[command(name = "decode", about = "Decode hex, base32, base58 or base85 data")]
+    Decode(DecodeOpts),
+    #[command(name = "hash", about = "Compute or check file digests")]
+    Hash(HashOpts),
+    #[command(
+        name = "hexdump",
+        about = "Show bytes as an xxd style hex dump, or reverse one"
+    )]
+    Hexdump(HexdumpOpts),
+    #[command(
+        name = "inspect",
+        about = "Detect and decode layers of encoding in a string or file"
+    )]
+    Inspect(InspectOpts),
     #[command(subcommand, about = "Text sign/verify")]
     Text(TextSubCommand),
     #[command(subcommand, about = "HTTP server")]
     Http(HttpSubCommand),
     #[command(subcommand, about = "JWT encode/decode")]
     Jwt(JwtSubCommand),
+    #[command(subcommand, about = "URL encode/decode/parse")]
+    Url(UrlSubCommand),
+    #[command(subcommand, about = "QR code generation")]
+    Qr(QrSubCommand),
+    #[command(subcommand, about = "UUID, ULID, nanoid and snowflake ids")]
+    Id(IdSubCommand),
+    #[command(subcommand, about = "Encrypted local secrets vault")]
+    Vault(VaultSubCommand),
 }
 
 fn verify_file(filename: &str) -> anyhow::Result<String, &'static str> {
     // if filename is "-", it means read from stdin
-    if filename == "-" || Path::new(filename).exists() {
+    // "vault:name" references a secret in the vault
+    if filename == "-" || filename.starts_with(VAULT_REF_PREFIX) || Path::new(filename).exists() {
         Ok(filename.into())
     } else {
         Err("Input file does not exist")
     }
 }
 
+/// Print a verify result, exit code is 0 when valid, 1 when invalid and 2 when
+/// the check couldn't run (unreadable key, malformed signature, ...)
+fn report_verify(
+    result: anyhow::Result<VerifyResult>,
+    algorithm: &str,
+    json: bool,
+) -> anyhow::Result<()> {
+    let (result, code) = match result {
+        Ok(result) if result.valid => (result, 0),
+        Ok(result) => (result, 1),
+        Err(e) => (VerifyResult::invalid(algorithm, format!("{:#}", e)), 2),
+    };
+    if json {
+        println!("{}", serde_json::to_string(&result)?);
+    } else {
+        if let Some(claims) = &result.claims {
+            println!("{}", claims);
+        }
+        println!("{}", result.valid);
+        if let Some(reason) = &result.reason {
+            eprintln!("{}", reason);
+        }
+    }
+    match code {
+        0 => Ok(()),
+        code => Err(CmdExit(code).into()),
+    }
+}
+
 fn verify_path(path: &str) -> anyhow::Result<PathBuf, &'static str> {
     let p = Path::new(path);
     if Path::new(path).exists() && p.is_dir() {
@@ -63,6 +139,7 @@ mod tests {
         assert_eq!(verify_file("-"), Ok("-".into()));
         assert_eq!(verify_file("*"), Err("Input file does not exist"));
         assert_eq!(verify_file("Cargo.toml"), Ok("Cargo.toml".into()));
+        assert_eq!(verify_file("vault:jwt"), Ok("vault:jwt".into()));
         assert_eq!(verify_file("nonexistent"), Err("Input file does not exist"));
     }
 }
@@ -0,0 +1,159 @@
+use std::fmt;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use clap::Parser;
+use enum_dispatch::enum_dispatch;
+
+use crate::utils::get_writer;
+use crate::{process_qr_encode, CmdExecutor};
+
+use super::verify_file;
+
+#[derive(Debug, Parser)]
+#[enum_dispatch(CmdExecutor)]
+pub enum QrSubCommand {
+    #[command(name = "encode", about = "Render text as a QR code")]
+    Encode(QrEncodeOpts),
+}
+
+#[derive(Debug, Copy, Clone)]
+pub enum QrEcLevel {
+    Low,
+    Medium,
+    Quartile,
+    High,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub enum QrFormat {
+    Terminal,
+    Png,
+    Svg,
+}
+
+#[derive(Debug, Parser)]
+pub struct QrEncodeOpts {
+    #[arg(short, long, value_parser = verify_file, default_value = "-")]
+    pub input: String,
+    #[arg(
+        long,
+        value_parser = parse_ec_level,
+        default_value = "medium",
+        help = "Error correction: low, medium, quartile or high"
+    )]
+    pub ec: QrEcLevel,
+    #[arg(
+        long,
+        value_parser = parse_qr_format,
+        help = "terminal, png or svg, defaults to the output file extension"
+    )]
+    pub format: Option<QrFormat>,
+    #[arg(
+        long,
+        value_parser = clap::value_parser!(u32).range(1..=64),
+        default_value_t = 8,
+        help = "Pixels per module for png and svg, 1-64"
+    )]
+    pub scale: u32,
+    #[arg(long, help = "Draw dark modules as blocks, for light terminals")]
+    pub invert: bool,
+    #[arg(short, long, help = "Write to a file instead of stdout")]
+    pub output: Option<PathBuf>,
+}
+
+impl CmdExecutor for QrEncodeOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let format = match (self.format, self.output.as_deref()) {
+            (Some(format), _) => format,
+            (None, Some(path)) => format_from_path(path)?,
+            (None, None) => QrFormat::Terminal,
+        };
+        let qr = process_qr_encode(&self.input, self.ec, format, self.scale, self.invert)?;
+        let mut writer = get_writer(self.output.as_deref())?;
+        writer.write_all(&qr)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+fn format_from_path(path: &Path) -> anyhow::Result<QrFormat> {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("png") => Ok(QrFormat::Png),
+        Some("svg") => Ok(QrFormat::Svg),
+        Some("txt") => Ok(QrFormat::Terminal),
+        _ => anyhow::bail!(
+            "can't tell the format from {}, use --format",
+            path.display()
+        ),
+    }
+}
+
+fn parse_ec_level(level: &str) -> anyhow::Result<QrEcLevel, anyhow::Error> {
+    level.parse()
+}
+
+fn parse_qr_format(format: &str) -> anyhow::Result<QrFormat, anyhow::Error> {
+    format.parse()
+}
+
+impl FromStr for QrEcLevel {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "low" => Ok(QrEcLevel::Low),
+            "medium" => Ok(QrEcLevel::Medium),
+            "quartile" => Ok(QrEcLevel::Quartile),
+            "high" => Ok(QrEcLevel::High),
+            _ => anyhow::bail!("Unsupported error correction level"),
+        }
+    }
+}
+
+impl From<QrEcLevel> for &'static str {
+    fn from(level: QrEcLevel) -> &'static str {
+        match level {
+            QrEcLevel::Low => "low",
+            QrEcLevel::Medium => "medium",
+            QrEcLevel::Quartile => "quartile",
+            QrEcLevel::High => "high",
+        }
+    }
+}
+
+impl fmt::Display for QrEcLevel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", Into::<&str>::into(*self))
+    }
+}
+
+impl FromStr for QrFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "terminal" => Ok(QrFormat::Terminal),
+            "png" => Ok(QrFormat::Png),
+            "svg" => Ok(QrFormat::Svg),
+            _ => anyhow::bail!("Unsupported QR code format"),
+        }
+    }
+}
+
+impl From<QrFormat> for &'static str {
+    fn from(format: QrFormat) -> &'static str {
+        match format {
+            QrFormat::Terminal => "terminal",
+            QrFormat::Png => "png",
+            QrFormat::Svg => "svg",
+        }
+    }
+}
+
+impl fmt::Display for QrFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", Into::<&str>::into(*self))
+    }
+}
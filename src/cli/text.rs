@@ -1,3 +1,4 @@
+use std::io::Write;
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::{fmt, fs};
@@ -6,11 +7,16 @@ use clap::Parser;
 use enum_dispatch::enum_dispatch;
 
 use crate::{
-    process_generate_key, process_text_decrypt, process_text_encrypt, process_text_sign,
-    process_text_verify, CmdExecutor,
+    process_generate_key, process_key_info, process_manifest_sign, process_manifest_verify,
+    process_text_combine, process_text_decrypt, process_text_decrypt_passphrase,
+    process_text_decrypt_with, process_text_derive, process_text_encrypt, process_text_encrypt_age,
+    process_text_encrypt_to, process_text_sign, process_text_split, process_text_verify,
+    CmdExecutor,
 };
 
-use super::{verify_file, verify_path};
+use crate::utils::{get_passphrase, prompt_passphrase, write_secret_file};
+
+use super::{report_verify, verify_file, verify_path};
 
 #[derive(Debug, Parser)]
 #[enum_dispatch(CmdExecutor)]
@@ -19,12 +25,28 @@ pub enum TextSubCommand {
     Sign(TextSignOpts),
     #[command(about = "Verify a signed message")]
     Verify(TextVerifyOpts),
-    #[command(about = "Generate a new key pair")]
+    #[command(about = "Generate a new signing key (pair) or an x25519 encryption key pair")]
     Generate(TextKeyGenerateOpts),
-    #[command(about = "Encrypt a key with chacha20poly1305 and output base64 encoded text")]
+    #[command(
+        about = "Encrypt with a chacha20poly1305 key or to x25519 recipients and output base64 encoded text"
+    )]
     Encrypt(TextEncryptOpts),
-    #[command(about = "Decrypt a key with chacha20poly1305 from base64 encoded text")]
+    #[command(
+        about = "Decrypt base64 encoded text with a chacha20poly1305 key or an x25519 identity"
+    )]
     Decrypt(TextDecryptOpts),
+    #[command(about = "Hash every file in a directory and sign the manifest with ed25519")]
+    SignManifest(TextSignManifestOpts),
+    #[command(about = "Verify a signed manifest against the files in a directory")]
+    VerifyManifest(TextVerifyManifestOpts),
+    #[command(about = "Show a key's algorithm, public key and fingerprint")]
+    KeyInfo(TextKeyInfoOpts),
+    #[command(about = "Split a secret into shamir shares, any threshold of them recover it")]
+    Split(TextSplitOpts),
+    #[command(about = "Recover a secret from shamir shares")]
+    Combine(TextCombineOpts),
+    #[command(about = "Derive a purpose specific key from a master key")]
+    Derive(TextDeriveOpts),
 }
 
 #[derive(Debug, Parser)]
@@ -35,6 +57,14 @@ pub struct TextSignOpts {
     pub key: String,
     #[arg(long, value_parser = parse_format, default_value = "blake3")]
     pub format: TextSignFormat,
+    #[arg(long, value_parser = parse_signature_encoding, default_value = "raw")]
+    pub sig_encoding: SignatureEncoding,
+    #[arg(long, value_parser = parse_text_encoding, default_value = "base64url")]
+    pub text_encoding: TextEncoding,
+    #[arg(long, help = "Write the signature to a file instead of stdout")]
+    pub sig_file: Option<String>,
+    #[arg(long, help = "Trusted comment for minisign signatures")]
+    pub trusted_comment: Option<String>,
 }
 
 #[derive(Debug, Parser)]
@@ -45,68 +75,298 @@ pub struct TextVerifyOpts {
     pub key: String,
     #[arg(long, value_parser = parse_format, default_value = "blake3")]
     pub format: TextSignFormat,
-    #[arg(short, long)]
-    pub sig: String,
+    #[arg(
+        short,
+        long,
+        required_unless_present = "sig_file",
+        conflicts_with = "sig_file"
+    )]
+    pub sig: Option<String>,
+    #[arg(long, value_parser = verify_file, help = "Read the signature from a file")]
+    pub sig_file: Option<String>,
+    #[arg(long, value_parser = parse_text_encoding, default_value = "base64url")]
+    pub text_encoding: TextEncoding,
+    #[arg(long, help = "Print the result as JSON")]
+    pub json: bool,
 }
 
 #[derive(Debug, Parser)]
 pub struct TextKeyGenerateOpts {
-    #[arg(long, default_value = "blake3", value_parser = parse_format)]
-    pub format: TextSignFormat,
+    #[arg(long, default_value = "blake3", value_parser = parse_key_type)]
+    pub format: TextKeyType,
     #[arg(short, long, value_parser = verify_path)]
     pub output: PathBuf,
+    #[arg(long, value_parser = parse_key_format, default_value = "raw")]
+    pub key_format: KeyFormat,
+    #[arg(
+        long,
+        help = "Encrypt the private key with a passphrase (from RCLI_KEY_PASSPHRASE or a prompt)"
+    )]
+    pub encrypt: bool,
+}
+
+#[derive(Debug, Parser)]
+pub struct TextSignManifestOpts {
+    #[arg(value_parser = verify_path)]
+    pub dir: PathBuf,
+    #[arg(short, long, value_parser = verify_file)]
+    pub key: String,
+    #[arg(short, long, default_value = "manifest.txt")]
+    pub output: String,
+    #[arg(long, help = "Signature file, defaults to <output>.sig")]
+    pub sig_file: Option<String>,
+}
+
+#[derive(Debug, Parser)]
+pub struct TextVerifyManifestOpts {
+    #[arg(value_parser = verify_path)]
+    pub dir: PathBuf,
+    #[arg(short, long, value_parser = verify_file, default_value = "manifest.txt")]
+    pub manifest: String,
+    #[arg(short, long, value_parser = verify_file)]
+    pub key: String,
+    #[arg(long, help = "Signature file, defaults to <manifest>.sig")]
+    pub sig_file: Option<String>,
+}
+
+#[derive(Debug, Parser)]
+pub struct TextKeyInfoOpts {
+    #[arg(value_parser = verify_file)]
+    pub key: String,
+    #[arg(long, value_parser = parse_key_type, help = "Key algorithm, detected when omitted")]
+    pub format: Option<TextKeyType>,
+    #[arg(
+        long,
+        help = "Only print the public key, e.g. to derive it from a private key"
+    )]
+    pub public: bool,
+}
+
+#[derive(Debug, Parser)]
+pub struct TextSplitOpts {
+    #[arg(short, long, value_parser = verify_file, default_value = "-")]
+    pub input: String,
+    #[arg(long, value_parser = clap::value_parser!(u8).range(2..), default_value_t = 5)]
+    pub shares: u8,
+    #[arg(long, value_parser = clap::value_parser!(u8).range(2..), default_value_t = 3)]
+    pub threshold: u8,
+    #[arg(
+        short,
+        long,
+        value_parser = verify_path,
+        help = "Write share-<n>.txt files to this directory instead of stdout"
+    )]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Parser)]
+pub struct TextCombineOpts {
+    #[arg(value_parser = verify_file, required = true, help = "Share files, each may hold several shares")]
+    pub shares: Vec<String>,
+    #[arg(short, long, help = "Write the secret to a file instead of stdout")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Parser)]
+pub struct TextDeriveOpts {
+    #[arg(short, long, value_parser = verify_file, help = "Master key file or vault:name")]
+    pub master: String,
+    #[arg(short, long, help = "What the key is for, e.g. \"service-a/encrypt\"")]
+    pub context: String,
+    #[arg(long, value_parser = parse_kdf, default_value = "blake3")]
+    pub kdf: Kdf,
+    #[arg(long, value_parser = parse_derive_key_type, default_value = "blake3")]
+    pub key_type: DeriveKeyType,
+    #[arg(long, value_parser = parse_key_format, default_value = "raw")]
+    pub key_format: KeyFormat,
+    #[arg(short, long, help = "Write the key to a file instead of stdout")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub enum Kdf {
+    Blake3,
+    HkdfSha256,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub enum DeriveKeyType {
+    Blake3,
+    Ed25519,
+    ChaCha20Poly1305,
 }
 
 #[derive(Debug, Copy, Clone)]
 pub enum TextSignFormat {
     Blake3,
     Ed25519,
+    P256,
+    Secp256k1,
+    RsaPss,
+    Minisign,
+    HmacSha256,
+    HmacSha512,
+}
+
+/// Every key `text generate` and `text key-info` know, the sign formats plus x25519
+/// which only encrypts
+#[derive(Debug, Copy, Clone)]
+pub enum TextKeyType {
+    Blake3,
+    Ed25519,
+    P256,
+    Secp256k1,
+    RsaPss,
+    Minisign,
+    HmacSha256,
+    HmacSha512,
+    X25519,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub enum SignatureEncoding {
+    Raw,
+    Der,
+}
+
+/// How a binary signature is written as text
+#[derive(Debug, Copy, Clone)]
+pub enum TextEncoding {
+    Base64Url,
+    Base64,
+    Hex,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub enum KeyFormat {
+    Raw,
+    Pem,
+    OpenSsh,
+    Age,
 }
 
 #[derive(Debug, Parser)]
 pub struct TextEncryptOpts {
     #[arg(short, long, value_parser = verify_file, default_value = "-")]
     pub input: String,
-    #[arg(short, long, value_parser = verify_file)]
-    pub key: String,
+    #[arg(
+        short,
+        long,
+        value_parser = verify_file,
+        required_unless_present_any = ["recipient", "passphrase"]
+    )]
+    pub key: Option<String>,
+    #[arg(
+        short,
+        long,
+        conflicts_with = "key",
+        help = "x25519 public key from `text generate --format x25519` (file, hex, base64 or age1...), can be repeated"
+    )]
+    pub recipient: Vec<String>,
+    #[arg(
+        long,
+        conflicts_with = "key",
+        help = "Write the age v1 format instead of base64 encoded text"
+    )]
+    pub age: bool,
+    #[arg(long, requires = "age", help = "ASCII armor the age output")]
+    pub armor: bool,
+    #[arg(
+        long,
+        requires = "age",
+        conflicts_with_all = ["key", "recipient"],
+        help = "Encrypt with a passphrase (from RCLI_AGE_PASSPHRASE or a prompt)"
+    )]
+    pub passphrase: bool,
 }
 
 #[derive(Debug, Parser)]
 pub struct TextDecryptOpts {
     #[arg(short, long, value_parser = verify_file, default_value = "-")]
     pub input: String,
-    #[arg(short, long, value_parser = verify_file)]
-    pub key: String,
+    #[arg(
+        short,
+        long,
+        value_parser = verify_file,
+        required_unless_present_any = ["identity", "passphrase"]
+    )]
+    pub key: Option<String>,
+    #[arg(
+        long,
+        value_parser = verify_file,
+        conflicts_with = "key",
+        help = "x25519 secret key from `text generate --format x25519` or an age identity file"
+    )]
+    pub identity: Option<String>,
+    #[arg(
+        long,
+        conflicts_with_all = ["key", "identity"],
+        help = "Decrypt an age file with a passphrase (from RCLI_AGE_PASSPHRASE or a prompt)"
+    )]
+    pub passphrase: bool,
 }
 
 impl CmdExecutor for TextSignOpts {
     async fn execute(self) -> anyhow::Result<()> {
-        let signed = process_text_sign(&self.input, &self.key, self.format)?;
-        println!("{}", signed);
+        let signed = process_text_sign(
+            &self.input,
+            &self.key,
+            self.format,
+            self.sig_encoding,
+            self.text_encoding,
+            self.trusted_comment.as_deref(),
+        )?;
+        match self.sig_file {
+            Some(sig_file) => fs::write(sig_file, format!("{}\n", signed))?,
+            None => println!("{}", signed),
+        }
         Ok(())
     }
 }
 
 impl CmdExecutor for TextVerifyOpts {
     async fn execute(self) -> anyhow::Result<()> {
-        let verified = process_text_verify(&self.input, &self.key, self.format, &self.sig)?;
-        println!("{}", verified);
-        Ok(())
+        let sig = match (self.sig, self.sig_file) {
+            (Some(sig), _) => sig,
+            (None, Some(sig_file)) => match fs::read_to_string(sig_file) {
+                Ok(sig) => sig.trim().to_string(),
+                Err(e) => return report_verify(Err(e.into()), &self.format.to_string(), self.json),
+            },
+            (None, None) => anyhow::bail!("either --sig or --sig-file is required"),
+        };
+        let result = process_text_verify(
+            &self.input,
+            &self.key,
+            self.format,
+            &sig,
+            self.text_encoding,
+        );
+        report_verify(result, &self.format.to_string(), self.json)
     }
 }
 
 impl CmdExecutor for TextKeyGenerateOpts {
     async fn execute(self) -> anyhow::Result<()> {
-        let key = process_generate_key(self.format)?;
+        let passphrase = if self.encrypt {
+            Some(get_passphrase(true)?)
+        } else {
+            None
+        };
+        let key = process_generate_key(self.format, self.key_format, passphrase.as_deref())?;
         match self.format {
-            TextSignFormat::Blake3 => {
-                let name = self.output.join("blake3.txt");
-                fs::write(name, &key[0])?;
+            TextKeyType::Blake3 | TextKeyType::HmacSha256 | TextKeyType::HmacSha512 => {
+                let name = self.output.join(format!("{}.txt", self.format));
+                write_secret_file(name, &key[0])?;
+            }
+            TextKeyType::Minisign => {
+                let name = self.output;
+                write_secret_file(name.join("minisign.key"), &key[0])?;
+                fs::write(name.join("minisign.pub"), &key[1])?;
             }
-            TextSignFormat::Ed25519 => {
+            _ => {
                 let name = self.output;
-                fs::write(name.join("ed25519.sk"), &key[0])?;
-                fs::write(name.join("ed25519.pk"), &key[1])?;
+                write_secret_file(name.join(format!("{}.sk", self.format)), &key[0])?;
+                fs::write(name.join(format!("{}.pk", self.format)), &key[1])?;
             }
         }
         Ok(())
@@ -115,7 +375,24 @@ impl CmdExecutor for TextKeyGenerateOpts {
 
 impl CmdExecutor for TextEncryptOpts {
     async fn execute(self) -> anyhow::Result<()> {
-        let encrypted = process_text_encrypt(&self.input, &self.key)?;
+        if self.age {
+            let passphrase = match self.passphrase {
+                true => Some(age_passphrase(true)?),
+                false => None,
+            };
+            let encrypted = process_text_encrypt_age(
+                &self.input,
+                &self.recipient,
+                passphrase.as_deref(),
+                self.armor,
+            )?;
+            std::io::stdout().write_all(&encrypted)?;
+            return Ok(());
+        }
+        let encrypted = match self.key {
+            Some(key) => process_text_encrypt(&self.input, &key)?,
+            None => process_text_encrypt_to(&self.input, &self.recipient)?,
+        };
         println!("{}", encrypted);
         Ok(())
     }
@@ -123,21 +400,172 @@ impl CmdExecutor for TextEncryptOpts {
 
 impl CmdExecutor for TextDecryptOpts {
     async fn execute(self) -> anyhow::Result<()> {
-        let decrypted = process_text_decrypt(&self.input, &self.key)?;
-        println!("{}", decrypted);
+        let decrypted = match (self.key, self.identity) {
+            (Some(key), _) => {
+                let decrypted = process_text_decrypt(&self.input, &key)?;
+                println!("{}", decrypted);
+                return Ok(());
+            }
+            (None, Some(identity)) => process_text_decrypt_with(&self.input, &identity)?,
+            (None, None) => process_text_decrypt_passphrase(&self.input, &age_passphrase(false)?)?,
+        };
+        std::io::stdout().write_all(&decrypted)?;
+        Ok(())
+    }
+}
+
+impl CmdExecutor for TextSignManifestOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let sig_file = self
+            .sig_file
+            .unwrap_or_else(|| format!("{}.sig", self.output));
+        let (manifest, sig) =
+            process_manifest_sign(&self.dir, &self.key, &[&self.output, &sig_file])?;
+        fs::write(&self.output, manifest)?;
+        fs::write(&sig_file, format!("{}\n", sig))?;
+        eprintln!(
+            "Manifest written to {}, signature to {}",
+            self.output, sig_file
+        );
+        Ok(())
+    }
+}
+
+impl CmdExecutor for TextVerifyManifestOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let sig_file = self
+            .sig_file
+            .unwrap_or_else(|| format!("{}.sig", self.manifest));
+        let sig = fs::read_to_string(&sig_file)?;
+        let report = process_manifest_verify(
+            &self.dir,
+            &self.manifest,
+            &self.key,
+            &sig,
+            &[&self.manifest, &sig_file],
+        )?;
+        for path in &report.tampered {
+            println!("tampered: {}", path);
+        }
+        for path in &report.missing {
+            println!("missing: {}", path);
+        }
+        for path in &report.extra {
+            println!("extra: {}", path);
+        }
+        if !report.is_valid() {
+            anyhow::bail!("manifest verification failed");
+        }
+        println!("OK");
+        Ok(())
+    }
+}
+
+impl CmdExecutor for TextKeyInfoOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let info = process_key_info(&self.key, self.format)?;
+        if !self.public {
+            // the randomart already ends with a newline
+            print!("{}", info);
+            return Ok(());
+        }
+        match info.public_key {
+            Some(public_key) => println!("{}", public_key),
+            None => anyhow::bail!(
+                "{} keys are shared secrets without a public key",
+                info.algorithm
+            ),
+        }
+        Ok(())
+    }
+}
+
+impl CmdExecutor for TextSplitOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let shares = process_text_split(&self.input, self.shares, self.threshold)?;
+        match self.output {
+            Some(dir) => {
+                for (i, share) in shares.iter().enumerate() {
+                    write_secret_file(dir.join(format!("share-{}.txt", i + 1)), share.as_bytes())?;
+                }
+            }
+            None => println!("{}", shares.join("\n")),
+        }
+        Ok(())
+    }
+}
+
+impl CmdExecutor for TextCombineOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let secret = process_text_combine(&self.shares)?;
+        match self.output {
+            Some(output) => write_secret_file(output, &secret)?,
+            None => std::io::stdout().write_all(&secret)?,
+        }
         Ok(())
     }
 }
 
+impl CmdExecutor for TextDeriveOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let key = process_text_derive(
+            &self.master,
+            &self.context,
+            self.kdf,
+            self.key_type,
+            self.key_format,
+        )?;
+        match self.output {
+            Some(output) => write_secret_file(output, &key)?,
+            None => std::io::stdout().write_all(&key)?,
+        }
+        Ok(())
+    }
+}
+
+fn age_passphrase(confirm: bool) -> anyhow::Result<String> {
+    prompt_passphrase("RCLI_AGE_PASSPHRASE", "Age passphrase: ", confirm)
+}
+
 fn parse_format(format: &str) -> anyhow::Result<TextSignFormat, anyhow::Error> {
     format.parse()
 }
 
+fn parse_key_type(key_type: &str) -> anyhow::Result<TextKeyType, anyhow::Error> {
+    key_type.parse()
+}
+
+fn parse_signature_encoding(encoding: &str) -> anyhow::Result<SignatureEncoding, anyhow::Error> {
+    encoding.parse()
+}
+
+fn parse_text_encoding(encoding: &str) -> anyhow::Result<TextEncoding, anyhow::Error> {
+    encoding.parse()
+}
+
+fn parse_kdf(kdf: &str) -> anyhow::Result<Kdf, anyhow::Error> {
+    kdf.parse()
+}
+
+fn parse_derive_key_type(key_type: &str) -> anyhow::Result<DeriveKeyType, anyhow::Error> {
+    key_type.parse()
+}
+
+fn parse_key_format(format: &str) -> anyhow::Result<KeyFormat, anyhow::Error> {
+    format.parse()
+}
+
 impl From<TextSignFormat> for &'static str {
     fn from(format: TextSignFormat) -> &'static str {
         match format {
             TextSignFormat::Blake3 => "blake3",
             TextSignFormat::Ed25519 => "ed25519",
+            TextSignFormat::P256 => "p256",
+            TextSignFormat::Secp256k1 => "secp256k1",
+            TextSignFormat::RsaPss => "rsa-pss",
+            TextSignFormat::Minisign => "minisign",
+            TextSignFormat::HmacSha256 => "hmac-sha256",
+            TextSignFormat::HmacSha512 => "hmac-sha512",
         }
     }
 }
@@ -149,6 +577,12 @@ impl FromStr for TextSignFormat {
         match s {
             "blake3" => Ok(TextSignFormat::Blake3),
             "ed25519" => Ok(TextSignFormat::Ed25519),
+            "p256" => Ok(TextSignFormat::P256),
+            "secp256k1" => Ok(TextSignFormat::Secp256k1),
+            "rsa-pss" => Ok(TextSignFormat::RsaPss),
+            "minisign" => Ok(TextSignFormat::Minisign),
+            "hmac-sha256" => Ok(TextSignFormat::HmacSha256),
+            "hmac-sha512" => Ok(TextSignFormat::HmacSha512),
             _ => anyhow::bail!("Unsupported sign format"),
         }
     }
@@ -159,3 +593,187 @@ impl fmt::Display for TextSignFormat {
         write!(f, "{}", Into::<&str>::into(*self))
     }
 }
+
+impl From<TextKeyType> for &'static str {
+    fn from(key_type: TextKeyType) -> &'static str {
+        match key_type {
+            TextKeyType::Blake3 => "blake3",
+            TextKeyType::Ed25519 => "ed25519",
+            TextKeyType::P256 => "p256",
+            TextKeyType::Secp256k1 => "secp256k1",
+            TextKeyType::RsaPss => "rsa-pss",
+            TextKeyType::Minisign => "minisign",
+            TextKeyType::HmacSha256 => "hmac-sha256",
+            TextKeyType::HmacSha512 => "hmac-sha512",
+            TextKeyType::X25519 => "x25519",
+        }
+    }
+}
+
+impl FromStr for TextKeyType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "blake3" => Ok(TextKeyType::Blake3),
+            "ed25519" => Ok(TextKeyType::Ed25519),
+            "p256" => Ok(TextKeyType::P256),
+            "secp256k1" => Ok(TextKeyType::Secp256k1),
+            "rsa-pss" => Ok(TextKeyType::RsaPss),
+            "minisign" => Ok(TextKeyType::Minisign),
+            "hmac-sha256" => Ok(TextKeyType::HmacSha256),
+            "hmac-sha512" => Ok(TextKeyType::HmacSha512),
+            "x25519" => Ok(TextKeyType::X25519),
+            _ => anyhow::bail!("Unsupported key type"),
+        }
+    }
+}
+
+impl fmt::Display for TextKeyType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", Into::<&str>::into(*self))
+    }
+}
+
+impl From<KeyFormat> for &'static str {
+    fn from(format: KeyFormat) -> &'static str {
+        match format {
+            KeyFormat::Raw => "raw",
+            KeyFormat::Pem => "pem",
+            KeyFormat::OpenSsh => "openssh",
+            KeyFormat::Age => "age",
+        }
+    }
+}
+
+impl FromStr for KeyFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "raw" => Ok(KeyFormat::Raw),
+            "pem" => Ok(KeyFormat::Pem),
+            "openssh" => Ok(KeyFormat::OpenSsh),
+            "age" => Ok(KeyFormat::Age),
+            _ => anyhow::bail!("Unsupported key format"),
+        }
+    }
+}
+
+impl fmt::Display for KeyFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", Into::<&str>::into(*self))
+    }
+}
+
+impl From<SignatureEncoding> for &'static str {
+    fn from(encoding: SignatureEncoding) -> &'static str {
+        match encoding {
+            SignatureEncoding::Raw => "raw",
+            SignatureEncoding::Der => "der",
+        }
+    }
+}
+
+impl FromStr for SignatureEncoding {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "raw" => Ok(SignatureEncoding::Raw),
+            "der" => Ok(SignatureEncoding::Der),
+            _ => anyhow::bail!("Unsupported signature encoding"),
+        }
+    }
+}
+
+impl fmt::Display for SignatureEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", Into::<&str>::into(*self))
+    }
+}
+
+impl From<TextEncoding> for &'static str {
+    fn from(encoding: TextEncoding) -> &'static str {
+        match encoding {
+            TextEncoding::Base64Url => "base64url",
+            TextEncoding::Base64 => "base64",
+            TextEncoding::Hex => "hex",
+        }
+    }
+}
+
+impl FromStr for TextEncoding {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "base64url" => Ok(TextEncoding::Base64Url),
+            "base64" => Ok(TextEncoding::Base64),
+            "hex" => Ok(TextEncoding::Hex),
+            _ => anyhow::bail!("Unsupported text encoding"),
+        }
+    }
+}
+
+impl fmt::Display for TextEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", Into::<&str>::into(*self))
+    }
+}
+
+impl From<Kdf> for &'static str {
+    fn from(kdf: Kdf) -> &'static str {
+        match kdf {
+            Kdf::Blake3 => "blake3",
+            Kdf::HkdfSha256 => "hkdf-sha256",
+        }
+    }
+}
+
+impl FromStr for Kdf {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "blake3" => Ok(Kdf::Blake3),
+            "hkdf-sha256" => Ok(Kdf::HkdfSha256),
+            _ => anyhow::bail!("Unsupported kdf"),
+        }
+    }
+}
+
+impl fmt::Display for Kdf {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", Into::<&str>::into(*self))
+    }
+}
+
+impl From<DeriveKeyType> for &'static str {
+    fn from(key_type: DeriveKeyType) -> &'static str {
+        match key_type {
+            DeriveKeyType::Blake3 => "blake3",
+            DeriveKeyType::Ed25519 => "ed25519",
+            DeriveKeyType::ChaCha20Poly1305 => "chacha20poly1305",
+        }
+    }
+}
+
+impl FromStr for DeriveKeyType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "blake3" => Ok(DeriveKeyType::Blake3),
+            "ed25519" => Ok(DeriveKeyType::Ed25519),
+            "chacha20poly1305" => Ok(DeriveKeyType::ChaCha20Poly1305),
+            _ => anyhow::bail!("Unsupported key type"),
+        }
+    }
+}
+
+impl fmt::Display for DeriveKeyType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", Into::<&str>::into(*self))
+    }
+}
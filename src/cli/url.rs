@@ -0,0 +1,125 @@
+use std::fmt;
+use std::io::Write;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use clap::Parser;
+use enum_dispatch::enum_dispatch;
+
+use crate::utils::get_writer;
+use crate::{process_url_decode, process_url_encode, process_url_parse, CmdExecutor};
+
+use super::{parse_format, verify_file, OutputFormat};
+
+#[derive(Debug, Parser)]
+#[enum_dispatch(CmdExecutor)]
+pub enum UrlSubCommand {
+    #[command(name = "encode", about = "Percent-encode a string")]
+    Encode(UrlEncodeOpts),
+    #[command(name = "decode", about = "Decode a percent-encoded string")]
+    Decode(UrlDecodeOpts),
+    #[command(name = "parse", about = "Show the parts and decoded query of a URL")]
+    Parse(UrlParseOpts),
+}
+
+#[derive(Debug, Copy, Clone)]
+pub enum UrlEncoding {
+    Component,
+    Form,
+}
+
+#[derive(Debug, Parser)]
+pub struct UrlEncodeOpts {
+    #[arg(short, long, value_parser = verify_file, default_value = "-")]
+    pub input: String,
+    #[arg(
+        long,
+        value_parser = parse_url_encoding,
+        default_value = "component",
+        help = "component (RFC 3986) or form (application/x-www-form-urlencoded)"
+    )]
+    pub encoding: UrlEncoding,
+    #[arg(short, long, help = "Write to a file instead of stdout")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Parser)]
+pub struct UrlDecodeOpts {
+    #[arg(short, long, value_parser = verify_file, default_value = "-")]
+    pub input: String,
+    #[arg(long, value_parser = parse_url_encoding, default_value = "component")]
+    pub encoding: UrlEncoding,
+    #[arg(
+        short,
+        long,
+        help = "Write the decoded bytes to a file instead of stdout"
+    )]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Parser)]
+pub struct UrlParseOpts {
+    pub url: String,
+    #[arg(long, value_parser = parse_format, default_value = "json")]
+    pub format: OutputFormat,
+}
+
+impl CmdExecutor for UrlEncodeOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let mut writer = get_writer(self.output.as_deref())?;
+        process_url_encode(&self.input, &mut writer, self.encoding)?;
+        if self.output.is_none() {
+            writeln!(writer)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+impl CmdExecutor for UrlDecodeOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let mut writer = get_writer(self.output.as_deref())?;
+        process_url_decode(&self.input, &mut writer, self.encoding)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+impl CmdExecutor for UrlParseOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let parsed = process_url_parse(&self.url)?;
+        println!("{}", self.format.serialize(&parsed)?.trim_end());
+        Ok(())
+    }
+}
+
+fn parse_url_encoding(encoding: &str) -> anyhow::Result<UrlEncoding, anyhow::Error> {
+    encoding.parse()
+}
+
+impl FromStr for UrlEncoding {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "component" => Ok(UrlEncoding::Component),
+            "form" => Ok(UrlEncoding::Form),
+            _ => anyhow::bail!("Unsupported url encoding"),
+        }
+    }
+}
+
+impl From<UrlEncoding> for &'static str {
+    fn from(encoding: UrlEncoding) -> &'static str {
+        match encoding {
+            UrlEncoding::Component => "component",
+            UrlEncoding::Form => "form",
+        }
+    }
+}
+
+impl fmt::Display for UrlEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", Into::<&str>::into(*self))
+    }
+}
@@ -0,0 +1,135 @@
+use std::io::{IsTerminal, Read, Write};
+
+use clap::Parser;
+use enum_dispatch::enum_dispatch;
+
+use crate::utils::{get_reader, prompt_passphrase, read_line_input};
+use crate::{
+    get_vault_passphrase, process_vault_get, process_vault_init, process_vault_list,
+    process_vault_remove, process_vault_rotate, process_vault_set, vault_path, CmdExecutor,
+};
+
+use super::verify_file;
+
+#[derive(Debug, Parser)]
+#[enum_dispatch(CmdExecutor)]
+pub enum VaultSubCommand {
+    #[command(about = "Create a new empty vault")]
+    Init(VaultInitOpts),
+    #[command(about = "Store a secret read from a file, stdin or a prompt")]
+    Set(VaultSetOpts),
+    #[command(about = "Print a secret")]
+    Get(VaultGetOpts),
+    #[command(about = "List the secret names")]
+    List(VaultListOpts),
+    #[command(about = "Remove a secret")]
+    Rm(VaultRmOpts),
+    #[command(about = "Re-encrypt the vault with a new passphrase")]
+    Rotate(VaultRotateOpts),
+}
+
+#[derive(Debug, Parser)]
+pub struct VaultInitOpts {}
+
+#[derive(Debug, Parser)]
+pub struct VaultSetOpts {
+    pub name: String,
+    #[arg(
+        short,
+        long,
+        visible_alias = "file",
+        value_parser = verify_file,
+        help = "Store this file's exact bytes, e.g. a binary key"
+    )]
+    pub input: Option<String>,
+}
+
+#[derive(Debug, Parser)]
+pub struct VaultGetOpts {
+    pub name: String,
+}
+
+#[derive(Debug, Parser)]
+pub struct VaultListOpts {}
+
+#[derive(Debug, Parser)]
+pub struct VaultRmOpts {
+    pub name: String,
+}
+
+#[derive(Debug, Parser)]
+pub struct VaultRotateOpts {}
+
+impl CmdExecutor for VaultInitOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let path = vault_path();
+        process_vault_init(&path, &get_vault_passphrase(true)?)?;
+        eprintln!("Vault created at {}", path.display());
+        Ok(())
+    }
+}
+
+impl CmdExecutor for VaultSetOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let path = vault_path();
+        // the value never goes on the command line, where shell history and `ps` see it
+        let value = match self.input.as_deref() {
+            Some(input) if input != "-" => {
+                let mut buf = Vec::new();
+                get_reader(input)?.read_to_end(&mut buf)?;
+                buf
+            }
+            _ if std::io::stdin().is_terminal() => {
+                rpassword::prompt_password(format!("Value for {}: ", self.name))?.into_bytes()
+            }
+            // `echo tok | rcli vault set jwt` stores `tok`
+            _ => read_line_input("-")?,
+        };
+        if value.is_empty() {
+            anyhow::bail!("secret value can't be empty");
+        }
+        process_vault_set(&path, &get_vault_passphrase(false)?, &self.name, &value)?;
+        Ok(())
+    }
+}
+
+impl CmdExecutor for VaultGetOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let path = vault_path();
+        let value = process_vault_get(&path, &get_vault_passphrase(false)?, &self.name)?;
+        match String::from_utf8(value) {
+            Ok(value) => println!("{}", value),
+            Err(e) => std::io::stdout().write_all(e.as_bytes())?,
+        }
+        Ok(())
+    }
+}
+
+impl CmdExecutor for VaultListOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let path = vault_path();
+        for name in process_vault_list(&path, &get_vault_passphrase(false)?)? {
+            println!("{}", name);
+        }
+        Ok(())
+    }
+}
+
+impl CmdExecutor for VaultRmOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let path = vault_path();
+        process_vault_remove(&path, &get_vault_passphrase(false)?, &self.name)?;
+        Ok(())
+    }
+}
+
+impl CmdExecutor for VaultRotateOpts {
+    async fn execute(self) -> anyhow::Result<()> {
+        let path = vault_path();
+        let passphrase = get_vault_passphrase(false)?;
+        let new_passphrase =
+            prompt_passphrase("RCLI_VAULT_NEW_PASSPHRASE", "New vault passphrase: ", true)?;
+        process_vault_rotate(&path, &passphrase, &new_passphrase)?;
+        Ok(())
+    }
+}
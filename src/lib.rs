@@ -1,15 +1,37 @@
 use enum_dispatch::enum_dispatch;
 
 pub use cli::*;
-pub use process::b64::{process_decode, process_encode};
+pub use process::b64::{
+    process_armor, process_data_uri, process_dearmor, process_decode, process_encode,
+};
+pub use process::codec::{process_codec_decode, process_codec_encode};
 pub use process::csv_convert::process_csv;
+pub use process::derive::process_text_derive;
 pub use process::gen_pass::process_genpass;
+pub use process::hash::{process_hash, process_hash_check, CheckReport, CheckResult, HashEntry};
+pub use process::hexdump::{process_hexdump, process_hexdump_reverse};
 pub use process::http_serve::process_http_serve;
+pub use process::id::{process_id_generate, process_id_parse, IdInfo};
+pub use process::inspect::{process_inspect, Inspection, Layer};
 pub use process::jwt::{process_jwt_sign, process_jwt_verify};
+pub use process::key_info::{process_key_info, KeyInfo};
+pub use process::manifest::{process_manifest_sign, process_manifest_verify, ManifestReport};
+pub use process::qr::process_qr_encode;
+pub use process::shamir::{process_text_combine, process_text_split};
 pub use process::text::{
-    process_generate_key, process_text_decrypt, process_text_encrypt, process_text_sign,
-    process_text_verify,
+    process_generate_key, process_text_decrypt, process_text_decrypt_passphrase,
+    process_text_decrypt_with, process_text_encrypt, process_text_encrypt_age,
+    process_text_encrypt_to, process_text_sign, process_text_verify,
+};
+pub use process::url::{
+    process_url_decode, process_url_encode, process_url_parse, ParsedUrl, QueryParam,
 };
+pub use process::vault::{
+    get_vault_passphrase, process_vault_get, process_vault_init, process_vault_list,
+    process_vault_remove, process_vault_rotate, process_vault_set, resolve_vault_ref,
+    set_vault_path, vault_path, VAULT_REF_PREFIX,
+};
+pub use process::verify::VerifyResult;
 
 mod cli;
 mod process;
@@ -20,3 +42,16 @@ mod utils;
 pub trait CmdExecutor {
     async fn execute(self) -> anyhow::Result<()>;
 }
+
+/// Error for commands that report through a specific process exit code,
+/// whatever needed printing has already been printed
+#[derive(Debug)]
+pub struct CmdExit(pub i32);
+
+impl std::fmt::Display for CmdExit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "exit code {}", self.0)
+    }
+}
+
+impl std::error::Error for CmdExit {}
@@ -3,11 +3,20 @@
 use anyhow::Result;
 use clap::Parser;
 
-use rcli::{CmdExecutor, Opts};
+use rcli::{set_vault_path, CmdExecutor, CmdExit, Opts};
 
 #[tokio::main]
 async fn main() -> Result<()> {
     tracing_subscriber::fmt::init();
     let opts = Opts::parse();
-    opts.cmd.execute().await
+    if let Some(vault) = opts.vault {
+        set_vault_path(vault);
+    }
+    if let Err(e) = opts.cmd.execute().await {
+        if let Some(CmdExit(code)) = e.downcast_ref::<CmdExit>() {
+            std::process::exit(*code);
+        }
+        return Err(e);
+    }
+    Ok(())
 }
@@ -1,51 +1,608 @@
-use std::io::Read;
+use std::io::{self, BufRead, BufReader, Read, Write};
 
-use base64::engine::general_purpose::STANDARD;
-use base64::prelude::BASE64_URL_SAFE_NO_PAD;
+use base64::alphabet::{self, Alphabet};
+use base64::engine::general_purpose::{GeneralPurpose, NO_PAD, PAD, STANDARD};
+use base64::engine::DecodePaddingMode;
+use base64::read::DecoderReader;
+use base64::write::EncoderWriter;
 use base64::Engine as _;
 
 use crate::cli::Base64Format;
-use crate::utils::get_reader;
+use crate::utils::{get_reader, read_full};
 
-pub fn process_encode(input: &str, format: Base64Format) -> anyhow::Result<String> {
+const MIME_LINE_LEN: usize = 76;
+// RFC 7468 lines are 64 characters
+const ARMOR_LINE_LEN: usize = 64;
+// enough for every signature infer knows
+const MIME_SNIFF_LEN: usize = 8192;
+// OpenPGP's CRC-24 (RFC 4880 section 6.1)
+const CRC24_INIT: u32 = 0xb704ce;
+const CRC24_POLY: u32 = 0x1864cfb;
+
+/// Drops ASCII whitespace so line breaks (and the trailing newline) don't break decoding.
+/// With `url_safe_to_standard` the url safe characters are mapped onto the standard alphabet,
+/// `strip_padding` drops `=` so missing, complete or truncated padding all decode the same.
+struct CleanInput<R> {
+    inner: R,
+    url_safe_to_standard: bool,
+    strip_padding: bool,
+}
+
+impl<R: Read> Read for CleanInput<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let n = self.inner.read(buf)?;
+            if n == 0 {
+                return Ok(0);
+            }
+            let mut kept = 0;
+            for i in 0..n {
+                let b = match buf[i] {
+                    b'-' if self.url_safe_to_standard => b'+',
+                    b'_' if self.url_safe_to_standard => b'/',
+                    b => b,
+                };
+                let skip = b.is_ascii_whitespace() || (self.strip_padding && b == b'=');
+                if !skip {
+                    buf[kept] = b;
+                    kept += 1;
+                }
+            }
+            if kept > 0 {
+                return Ok(kept);
+            }
+        }
+    }
+}
+
+/// Breaks the output into lines of `width` characters
+struct LineWrap<W> {
+    inner: W,
+    width: usize,
+    line_ending: &'static [u8],
+    column: usize,
+}
+
+impl<W: Write> Write for LineWrap<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut written = 0;
+        while written < buf.len() {
+            // only break before more output, so there's no dangling line ending
+            if self.column == self.width {
+                self.inner.write_all(self.line_ending)?;
+                self.column = 0;
+            }
+            let n = (self.width - self.column).min(buf.len() - written);
+            self.inner.write_all(&buf[written..written + n])?;
+            self.column += n;
+            written += n;
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+fn alphabet(format: Base64Format) -> &'static Alphabet {
+    match format {
+        Base64Format::Standard | Base64Format::StandardNoPad | Base64Format::Mime => {
+            &alphabet::STANDARD
+        }
+        Base64Format::UrlSafe | Base64Format::UrlSafePad => &alphabet::URL_SAFE,
+        Base64Format::Bcrypt => &alphabet::BCRYPT,
+        Base64Format::Imap => &alphabet::IMAP_MUTF7,
+    }
+}
+
+fn engine(format: Base64Format) -> GeneralPurpose {
+    let config = match format {
+        Base64Format::Standard | Base64Format::UrlSafePad | Base64Format::Mime => PAD,
+        Base64Format::StandardNoPad
+        | Base64Format::UrlSafe
+        | Base64Format::Bcrypt
+        | Base64Format::Imap => NO_PAD,
+    };
+    GeneralPurpose::new(alphabet(format), config)
+}
+
+/// Stream the input through a base64 encoder into `writer`, memory use doesn't grow with the input.
+/// `wrap` breaks the output into lines, mime defaults to 76 character CRLF terminated lines.
+pub fn process_encode(
+    input: &str,
+    writer: &mut dyn Write,
+    format: Base64Format,
+    wrap: Option<usize>,
+) -> anyhow::Result<()> {
     let mut reader = get_reader(input)?;
-    let mut buf = Vec::new();
-    reader.read_to_end(&mut buf)?;
-    let encoded = match format {
-        Base64Format::Standard => STANDARD.encode(&buf),
-        Base64Format::UrlSafe => BASE64_URL_SAFE_NO_PAD.encode(&buf),
+    encode_reader(&mut reader, writer, format, wrap)
+}
+
+fn encode_reader(
+    reader: &mut dyn Read,
+    writer: &mut dyn Write,
+    format: Base64Format,
+    wrap: Option<usize>,
+) -> anyhow::Result<()> {
+    let (wrap, line_ending) = match format {
+        Base64Format::Mime => (wrap.or(Some(MIME_LINE_LEN)), &b"\r\n"[..]),
+        _ => (wrap, &b"\n"[..]),
     };
-    Ok(encoded)
+    let engine = engine(format);
+    match wrap.filter(|width| *width > 0) {
+        Some(width) => {
+            let wrapped = LineWrap {
+                inner: writer,
+                width,
+                line_ending,
+                column: 0,
+            };
+            let mut encoder = EncoderWriter::new(wrapped, &engine);
+            io::copy(reader, &mut encoder)?;
+            encoder.finish()?;
+        }
+        None => {
+            let mut encoder = EncoderWriter::new(writer, &engine);
+            io::copy(reader, &mut encoder)?;
+            encoder.finish()?;
+        }
+    }
+    Ok(())
+}
+
+/// Stream the decoded input into `writer`, whitespace in the input is ignored.
+/// `lenient` accepts either padding style, and for the standard and url safe formats
+/// either alphabet, so input of unknown origin decodes without picking the exact variant.
+pub fn process_decode(
+    input: &str,
+    writer: &mut dyn Write,
+    format: Base64Format,
+    lenient: bool,
+) -> anyhow::Result<()> {
+    let mut reader = get_reader(input)?;
+    decode_reader(&mut reader, writer, format, lenient)
 }
 
-pub fn process_decode(input: &str, format: Base64Format) -> anyhow::Result<Vec<u8>> {
-    let mut reader: Box<dyn Read> = get_reader(input)?;
-    let mut buf = String::new();
-    reader.read_to_string(&mut buf)?;
-    let buf = buf.trim(); // avoid trailing newline
-    let decoded = match format {
-        Base64Format::Standard => STANDARD.decode(buf)?,
-        Base64Format::UrlSafe => BASE64_URL_SAFE_NO_PAD.decode(buf)?,
+pub(crate) fn decode_reader(
+    reader: &mut dyn Read,
+    writer: &mut dyn Write,
+    format: Base64Format,
+    lenient: bool,
+) -> anyhow::Result<()> {
+    let standard_family = matches!(
+        format,
+        Base64Format::Standard
+            | Base64Format::StandardNoPad
+            | Base64Format::UrlSafe
+            | Base64Format::UrlSafePad
+            | Base64Format::Mime
+    );
+    let reader = CleanInput {
+        inner: reader,
+        url_safe_to_standard: lenient && standard_family,
+        strip_padding: lenient,
     };
+    let engine = match (lenient, standard_family) {
+        (false, _) => engine(format),
+        (true, true) => lenient_engine(&alphabet::STANDARD),
+        (true, false) => lenient_engine(alphabet(format)),
+    };
+    let mut decoder = DecoderReader::new(reader, &engine);
+    io::copy(&mut decoder, writer)?;
+    Ok(())
+}
+
+fn lenient_engine(alphabet: &Alphabet) -> GeneralPurpose {
+    let config = NO_PAD
+        .with_decode_padding_mode(DecodePaddingMode::RequireNone)
+        .with_decode_allow_trailing_bits(true);
+    GeneralPurpose::new(alphabet, config)
+}
+
+/// Write the input as a `data:` URI. Without `mime` the type is sniffed from the first bytes.
+pub fn process_data_uri(
+    input: &str,
+    writer: &mut dyn Write,
+    mime: Option<&str>,
+) -> anyhow::Result<()> {
+    let mut reader = get_reader(input)?;
+    let mut head = vec![0u8; MIME_SNIFF_LEN];
+    let n = read_full(&mut reader, &mut head)?;
+    head.truncate(n);
+    let mime = match mime {
+        Some(mime) => mime.to_string(),
+        None => sniff_mime(&head),
+    };
+    write!(writer, "data:{};base64,", mime)?;
+    let mut reader = io::Cursor::new(head).chain(reader);
+    encode_reader(&mut reader, writer, Base64Format::Standard, None)
+}
+
+/// Wrap the input in an RFC 7468 block, the base64 body is followed by an
+/// OpenPGP style `=` CRC-24 line so `process_dearmor` can catch corruption
+pub fn process_armor(input: &str, writer: &mut dyn Write, label: &str) -> anyhow::Result<()> {
+    validate_label(label)?;
+    let mut reader = Crc24Reader {
+        inner: get_reader(input)?,
+        crc: CRC24_INIT,
+        len: 0,
+    };
+    writeln!(writer, "-----BEGIN {}-----", label)?;
+    encode_reader(
+        &mut reader,
+        writer,
+        Base64Format::Standard,
+        Some(ARMOR_LINE_LEN),
+    )?;
+    if reader.len > 0 {
+        writeln!(writer)?;
+    }
+    writeln!(writer, "={}", crc24_encode(reader.crc))?;
+    writeln!(writer, "-----END {}-----", label)?;
+    Ok(())
+}
 
-    Ok(decoded)
+/// Decode the first armored block of the input. The CRC line is optional so plain
+/// PEM works too, `label` makes sure the block is the expected kind.
+pub fn process_dearmor(
+    input: &str,
+    writer: &mut dyn Write,
+    label: Option<&str>,
+) -> anyhow::Result<()> {
+    let reader = BufReader::new(get_reader(input)?);
+    let (_, data) = dearmor(reader, label)?;
+    writer.write_all(&data)?;
+    Ok(())
+}
+
+/// The label and decoded data of the first armored block
+pub(crate) fn dearmor(
+    reader: impl BufRead,
+    label: Option<&str>,
+) -> anyhow::Result<(String, Vec<u8>)> {
+    let mut block_label = None;
+    let mut body = String::new();
+    let mut checksum = None;
+    let mut complete = false;
+    for line in reader.lines() {
+        let line = line?;
+        let line = line.trim();
+        let Some(current) = &block_label else {
+            if let Some(found) = line
+                .strip_prefix("-----BEGIN ")
+                .and_then(|l| l.strip_suffix("-----"))
+            {
+                if label.is_some_and(|label| label != found) {
+                    anyhow::bail!("expected a {} block, found {}", label.unwrap(), found);
+                }
+                block_label = Some(found.to_string());
+            }
+            continue;
+        };
+        if line == format!("-----END {}-----", current) {
+            complete = true;
+            break;
+        }
+        // `=` plus four characters, a body line can be nothing but padding
+        if let Some(crc) = line.strip_prefix('=').filter(|crc| crc.len() == 4) {
+            checksum = Some(crc.to_string());
+        } else if !line.contains(':') {
+            // base64 has no ':', so those are `Key: value` headers
+            body.push_str(line);
+        }
+    }
+    let Some(found) = block_label else {
+        anyhow::bail!("no armored block found");
+    };
+    if !complete {
+        anyhow::bail!("armored block {} has no END line", found);
+    }
+    let data = STANDARD.decode(body)?;
+    if let Some(checksum) = checksum {
+        let crc = crc24_update(CRC24_INIT, &data);
+        if crc24_encode(crc) != checksum {
+            anyhow::bail!("armor checksum mismatch, the data is corrupted");
+        }
+    }
+    Ok((found, data))
+}
+
+// RFC 7468 labels are printable ASCII without '-', single spaces in between
+fn validate_label(label: &str) -> anyhow::Result<()> {
+    let valid = !label.is_empty()
+        && label.trim() == label
+        && !label.contains("  ")
+        && label
+            .chars()
+            .all(|c| c == ' ' || (c.is_ascii_graphic() && c != '-'));
+    if !valid {
+        anyhow::bail!("invalid armor label {:?}", label);
+    }
+    Ok(())
+}
+
+fn sniff_mime(head: &[u8]) -> String {
+    if let Some(kind) = infer::get(head) {
+        return kind.mime_type().to_string();
+    }
+    // the head can end in the middle of a character, that's still text
+    let text = match std::str::from_utf8(head) {
+        Ok(text) => text,
+        Err(e) if e.error_len().is_none() => {
+            std::str::from_utf8(&head[..e.valid_up_to()]).expect("prefix is valid utf-8")
+        }
+        Err(_) => return "application/octet-stream".to_string(),
+    };
+    let text = text.trim_start();
+    if text.starts_with("<svg") || (text.starts_with("<?xml") && text.contains("<svg")) {
+        "image/svg+xml".to_string()
+    } else {
+        "text/plain;charset=utf-8".to_string()
+    }
+}
+
+struct Crc24Reader<R> {
+    inner: R,
+    crc: u32,
+    len: u64,
+}
+
+impl<R: Read> Read for Crc24Reader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.crc = crc24_update(self.crc, &buf[..n]);
+        self.len += n as u64;
+        Ok(n)
+    }
+}
+
+fn crc24_update(mut crc: u32, data: &[u8]) -> u32 {
+    for b in data {
+        crc ^= (*b as u32) << 16;
+        for _ in 0..8 {
+            crc <<= 1;
+            if crc & 0x1000000 != 0 {
+                crc ^= CRC24_POLY;
+            }
+        }
+    }
+    crc & 0xffffff
+}
+
+fn crc24_encode(crc: u32) -> String {
+    STANDARD.encode(&crc.to_be_bytes()[1..])
 }
 
 #[cfg(test)]
 mod tests {
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+
     use super::*;
 
     #[test]
-    fn test_process_encode() {
+    fn test_process_encode() -> anyhow::Result<()> {
         let input = "Cargo.toml";
-        let format = Base64Format::Standard;
-        assert!(process_encode(input, format).is_ok());
+        let mut encoded = Vec::new();
+        process_encode(input, &mut encoded, Base64Format::Standard, None)?;
+        assert_eq!(encoded, STANDARD.encode(std::fs::read(input)?).into_bytes());
+        Ok(())
     }
 
     #[test]
-    fn test_process_decode() {
+    fn test_process_decode() -> anyhow::Result<()> {
         let input = "fixtures/b64.txt";
-        let format = Base64Format::Standard;
-        assert!(process_decode(input, format).is_ok());
+        let mut decoded = Vec::new();
+        process_decode(input, &mut decoded, Base64Format::Standard, false)?;
+        let content = std::fs::read_to_string(input)?;
+        assert_eq!(decoded, STANDARD.decode(content.trim())?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_binary_round_trip() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let data = [0xffu8, 0xfe, 0x00, 0x89, b'P', b'N', b'G', b'\r', b'\n'];
+        let input = dir.path().join("image.bin");
+        std::fs::write(&input, data)?;
+
+        let encoded = dir.path().join("image.b64");
+        let mut writer = crate::utils::get_writer(Some(&encoded))?;
+        process_encode(
+            input.to_str().unwrap(),
+            &mut writer,
+            Base64Format::Standard,
+            None,
+        )?;
+        writer.flush()?;
+
+        let decoded = dir.path().join("decoded.bin");
+        let mut writer = crate::utils::get_writer(Some(&decoded))?;
+        process_decode(
+            encoded.to_str().unwrap(),
+            &mut writer,
+            Base64Format::Standard,
+            false,
+        )?;
+        writer.flush()?;
+        assert_eq!(std::fs::read(decoded)?, data);
+        Ok(())
+    }
+
+    #[test]
+    fn test_stream_round_trip() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        // larger than the encoder and decoder buffers, with wrapped lines
+        let data = (0..200_000u32).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+        let encoded = URL_SAFE_NO_PAD.encode(&data);
+        let wrapped = encoded
+            .as_bytes()
+            .chunks(76)
+            .map(|line| format!("{}\n", std::str::from_utf8(line).unwrap()))
+            .collect::<String>();
+        let path = dir.path().join("data.b64");
+        std::fs::write(&path, wrapped)?;
+
+        let mut decoded = Vec::new();
+        process_decode(
+            path.to_str().unwrap(),
+            &mut decoded,
+            Base64Format::UrlSafe,
+            false,
+        )?;
+        assert_eq!(decoded, data);
+
+        std::fs::write(&path, b"not base64!")?;
+        let mut decoded = Vec::new();
+        assert!(process_decode(
+            path.to_str().unwrap(),
+            &mut decoded,
+            Base64Format::UrlSafe,
+            false
+        )
+        .is_err());
+        Ok(())
+    }
+
+    fn encode_bytes(data: &[u8], format: Base64Format, wrap: Option<usize>) -> String {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data");
+        std::fs::write(&path, data).unwrap();
+        let mut encoded = Vec::new();
+        process_encode(path.to_str().unwrap(), &mut encoded, format, wrap).unwrap();
+        String::from_utf8(encoded).unwrap()
+    }
+
+    fn decode_str(encoded: &str, format: Base64Format, lenient: bool) -> anyhow::Result<Vec<u8>> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("data.b64");
+        std::fs::write(&path, encoded)?;
+        let mut decoded = Vec::new();
+        process_decode(path.to_str().unwrap(), &mut decoded, format, lenient)?;
+        Ok(decoded)
+    }
+
+    #[test]
+    fn test_formats() -> anyhow::Result<()> {
+        let data = [0xfbu8, 0xff, 0xbf, 0x01];
+        let cases = [
+            (Base64Format::Standard, "+/+/AQ=="),
+            (Base64Format::StandardNoPad, "+/+/AQ"),
+            (Base64Format::UrlSafe, "-_-_AQ"),
+            (Base64Format::UrlSafePad, "-_-_AQ=="),
+            (Base64Format::Mime, "+/+/AQ=="),
+            (Base64Format::Bcrypt, "8989.O"),
+            (Base64Format::Imap, "+,+,AQ"),
+        ];
+        for (format, expected) in cases {
+            assert_eq!(encode_bytes(&data, format, None), expected, "{}", format);
+            assert_eq!(decode_str(expected, format, false)?, data, "{}", format);
+        }
+        assert!(decode_str("+/+/AQ", Base64Format::Standard, false).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_wrap() -> anyhow::Result<()> {
+        let data = vec![0u8; 60];
+        let mime = encode_bytes(&data, Base64Format::Mime, None);
+        assert_eq!(mime, format!("{}\r\n{}", "A".repeat(76), "A".repeat(4)));
+        assert_eq!(decode_str(&mime, Base64Format::Mime, false)?, data);
+
+        let wrapped = encode_bytes(&data[..30], Base64Format::Standard, Some(20));
+        assert_eq!(wrapped, format!("{}\n{}", "A".repeat(20), "A".repeat(20)));
+        assert_eq!(
+            encode_bytes(&data, Base64Format::Mime, Some(0)),
+            "A".repeat(80)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_lenient_decode() -> anyhow::Result<()> {
+        let data = [0xfbu8, 0xff, 0xbf, 0x01];
+        for encoded in ["+/+/AQ==", "+/+/AQ", "-_-_AQ", "-_-_AQ==", " +/-_\r\nAQ=\n"] {
+            assert_eq!(
+                decode_str(encoded, Base64Format::Standard, true)?,
+                data,
+                "{}",
+                encoded
+            );
+        }
+        assert_eq!(decode_str("+,+,AQ==", Base64Format::Imap, true)?, data);
+        assert!(decode_str("-_-_AQ", Base64Format::Standard, false).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_crc24() {
+        assert_eq!(crc24_update(CRC24_INIT, b"123456789"), 0x21cf02);
+        assert_eq!(crc24_encode(CRC24_INIT), "twTO");
+    }
+
+    #[test]
+    fn test_armor_round_trip() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let input = dir.path().join("data");
+        let armored = dir.path().join("data.asc");
+        let data = (0..100u8).collect::<Vec<_>>();
+        std::fs::write(&input, &data)?;
+        let mut out = Vec::new();
+        process_armor(input.to_str().unwrap(), &mut out, "RCLI MESSAGE")?;
+        let text = String::from_utf8(out)?;
+        let lines = text.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "-----BEGIN RCLI MESSAGE-----");
+        assert_eq!(lines[1].len(), ARMOR_LINE_LEN);
+        assert!(lines[lines.len() - 2].starts_with('='));
+        assert_eq!(lines[lines.len() - 1], "-----END RCLI MESSAGE-----");
+
+        let dearmor = |content: &str, label| -> anyhow::Result<Vec<u8>> {
+            std::fs::write(&armored, content)?;
+            let mut out = Vec::new();
+            process_dearmor(armored.to_str().unwrap(), &mut out, label)?;
+            Ok(out)
+        };
+        let with_preamble = format!("some text before\n{}", text);
+        assert_eq!(dearmor(&with_preamble, Some("RCLI MESSAGE"))?, data);
+        assert!(dearmor(&text, Some("PRIVATE KEY")).is_err());
+        assert!(dearmor(&text.replacen("AAEC", "AAED", 1), None).is_err());
+        // plain PEM without a checksum
+        let pem = pem_rfc7468::encode_string("TEST", pem_rfc7468::LineEnding::LF, b"pem")?;
+        assert_eq!(dearmor(&pem, Some("TEST"))?, b"pem");
+
+        std::fs::write(&input, b"")?;
+        let mut out = Vec::new();
+        process_armor(input.to_str().unwrap(), &mut out, "EMPTY")?;
+        assert_eq!(dearmor(std::str::from_utf8(&out)?, None)?, b"");
+        assert!(process_armor(input.to_str().unwrap(), &mut out, "-BAD-").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_data_uri() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("asset");
+        let uri = |content: &[u8], mime| -> anyhow::Result<String> {
+            std::fs::write(&path, content)?;
+            let mut out = Vec::new();
+            process_data_uri(path.to_str().unwrap(), &mut out, mime)?;
+            Ok(String::from_utf8(out)?)
+        };
+        let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+        assert!(uri(png, None)?.starts_with("data:image/png;base64,iVBORw0KGgo"));
+        assert_eq!(
+            uri(b"<svg xmlns='http://www.w3.org/2000/svg'/>", None)?,
+            format!(
+                "data:image/svg+xml;base64,{}",
+                STANDARD.encode("<svg xmlns='http://www.w3.org/2000/svg'/>")
+            )
+        );
+        assert_eq!(
+            uri(b"hi", None)?,
+            "data:text/plain;charset=utf-8;base64,aGk="
+        );
+        assert_eq!(uri(b"hi", Some("text/css"))?, "data:text/css;base64,aGk=");
+        assert!(uri(&[0xff, 0x00, 0x12], None)?.starts_with("data:application/octet-stream;"));
+        Ok(())
     }
 }
@@ -0,0 +1,288 @@
+use std::io::{Read, Write};
+
+use data_encoding::{
+    BASE32, BASE32HEX, BASE32HEX_NOPAD, BASE32_NOPAD, HEXLOWER, HEXLOWER_PERMISSIVE,
+};
+
+use crate::utils::{get_reader, read_full};
+use crate::Codec;
+
+// groups per chunk, chunks are cut on group boundaries so each one encodes on its own
+const CHUNK_GROUPS: usize = 4096;
+const ASCII85_OFFSET: u8 = b'!';
+const Z85_ALPHABET: &[u8; 85] =
+    b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ.-:+=^!/*?&<>()[]{}@%$#";
+
+impl Codec {
+    /// (raw, encoded) bytes per group, base58 has no groups
+    fn group_len(self) -> Option<(usize, usize)> {
+        match self {
+            Codec::Hex => Some((1, 2)),
+            Codec::Base32 | Codec::Base32Hex => Some((5, 8)),
+            Codec::Base58 => None,
+            Codec::Base85 | Codec::Z85 => Some((4, 5)),
+        }
+    }
+}
+
+/// Stream the input through the codec into `writer`. Base58 is one big number,
+/// so it's the only codec that reads the whole input first.
+pub fn process_codec_encode(
+    input: &str,
+    writer: &mut dyn Write,
+    codec: Codec,
+) -> anyhow::Result<()> {
+    let mut reader = get_reader(input)?;
+    let Some((raw_len, _)) = codec.group_len() else {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        writer.write_all(encode_chunk(&data, codec)?.as_bytes())?;
+        return Ok(());
+    };
+    // full buffers keep the chunks group aligned
+    let mut buf = vec![0u8; raw_len * CHUNK_GROUPS];
+    loop {
+        let n = read_full(&mut reader, &mut buf)?;
+        if n > 0 {
+            writer.write_all(encode_chunk(&buf[..n], codec)?.as_bytes())?;
+        }
+        if n < buf.len() {
+            return Ok(());
+        }
+    }
+}
+
+/// Stream the decoded input into `writer`. Whitespace is ignored, hex and base32 are
+/// case insensitive and base32 padding is optional, so TOTP style secrets decode as well.
+/// Ascii85 may be wrapped in Adobe's `<~ ~>`. Every chunk is decoded before it's written,
+/// but an error in a later chunk leaves the earlier ones written.
+pub fn process_codec_decode(
+    input: &str,
+    writer: &mut dyn Write,
+    codec: Codec,
+) -> anyhow::Result<()> {
+    let mut reader = get_reader(input)?;
+    let chunk_len = codec
+        .group_len()
+        .map(|(_, encoded_len)| encoded_len * CHUNK_GROUPS);
+    let mut pending = Vec::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    // non-whitespace bytes so far, and whether ascii85's closing `~` or `~>` was seen
+    let mut seen = 0usize;
+    let (mut closing, mut closed) = (false, false);
+    loop {
+        let n = reader.read(&mut buf)?;
+        for b in &buf[..n] {
+            if b.is_ascii_whitespace() {
+                continue;
+            }
+            seen += 1;
+            match (codec, *b) {
+                _ if closed => anyhow::bail!("invalid base85 input after the closing ~>"),
+                (_, b'>') if closing => closed = true,
+                _ if closing => anyhow::bail!("invalid base85 input, ~ must be followed by >"),
+                // '~' is not an ascii85 digit, so `<~` can only be the opening delimiter
+                (Codec::Base85, b'~') if seen == 2 && pending == b"<" => pending.clear(),
+                (Codec::Base85, b'~') => closing = true,
+                (Codec::Base32 | Codec::Base32Hex, b'=') => {}
+                (Codec::Base32 | Codec::Base32Hex, b) => pending.push(b.to_ascii_uppercase()),
+                // ascii85 shorthand for four zero bytes, only allowed between groups
+                (Codec::Base85, b'z') => {
+                    if !pending.len().is_multiple_of(5) {
+                        anyhow::bail!("invalid base85 input, 'z' inside a group");
+                    }
+                    pending.extend_from_slice(&[ASCII85_OFFSET; 5]);
+                }
+                (_, b) => pending.push(b),
+            }
+        }
+        if n == 0 {
+            break;
+        }
+        if let Some(chunk_len) = chunk_len {
+            if pending.len() >= chunk_len {
+                let aligned = pending.len() - pending.len() % chunk_len;
+                writer.write_all(&decode_chunk(&pending[..aligned], codec)?)?;
+                pending.drain(..aligned);
+            }
+        }
+    }
+    if closing && !closed {
+        anyhow::bail!("invalid base85 input, ~ must be followed by >");
+    }
+    writer.write_all(&decode_chunk(&pending, codec)?)?;
+    Ok(())
+}
+
+fn encode_chunk(data: &[u8], codec: Codec) -> anyhow::Result<String> {
+    let encoded = match codec {
+        Codec::Hex => HEXLOWER.encode(data),
+        Codec::Base32 => BASE32.encode(data),
+        Codec::Base32Hex => BASE32HEX.encode(data),
+        Codec::Base58 => bs58::encode(data).into_string(),
+        Codec::Base85 => base85_encode(data, false),
+        Codec::Z85 => {
+            if !data.len().is_multiple_of(4) {
+                anyhow::bail!("z85 input length must be a multiple of 4");
+            }
+            base85_encode(data, true)
+        }
+    };
+    Ok(encoded)
+}
+
+fn decode_chunk(data: &[u8], codec: Codec) -> anyhow::Result<Vec<u8>> {
+    let decoded = match codec {
+        Codec::Hex => HEXLOWER_PERMISSIVE.decode(data)?,
+        Codec::Base32 => BASE32_NOPAD.decode(data)?,
+        Codec::Base32Hex => BASE32HEX_NOPAD.decode(data)?,
+        Codec::Base58 => bs58::decode(data).into_vec()?,
+        Codec::Base85 => base85_decode(data, false)?,
+        Codec::Z85 => {
+            if !data.len().is_multiple_of(5) {
+                anyhow::bail!("z85 input length must be a multiple of 5");
+            }
+            base85_decode(data, true)?
+        }
+    };
+    Ok(decoded)
+}
+
+// ascii85 (without the <~ ~> delimiters, like python's a85encode) or ZeroMQ's z85,
+// a trailing partial group of n bytes becomes n + 1 characters
+fn base85_encode(data: &[u8], z85: bool) -> String {
+    let mut encoded = String::with_capacity(data.len().div_ceil(4) * 5);
+    for group in data.chunks(4) {
+        let mut bytes = [0u8; 4];
+        bytes[..group.len()].copy_from_slice(group);
+        let mut value = u32::from_be_bytes(bytes);
+        if !z85 && value == 0 && group.len() == 4 {
+            encoded.push('z');
+            continue;
+        }
+        let mut digits = [0u8; 5];
+        for digit in digits.iter_mut().rev() {
+            *digit = (value % 85) as u8;
+            value /= 85;
+        }
+        for digit in &digits[..group.len() + 1] {
+            let c = match z85 {
+                true => Z85_ALPHABET[*digit as usize],
+                false => digit + ASCII85_OFFSET,
+            };
+            encoded.push(c as char);
+        }
+    }
+    encoded
+}
+
+fn base85_decode(data: &[u8], z85: bool) -> anyhow::Result<Vec<u8>> {
+    let mut decoded = Vec::with_capacity(data.len() / 5 * 4 + 4);
+    for group in data.chunks(5) {
+        if group.len() == 1 {
+            anyhow::bail!("invalid base85 input, truncated group");
+        }
+        let mut value = 0u64;
+        for i in 0..5 {
+            // a partial group is padded with the highest digit
+            let digit = match group.get(i) {
+                Some(c) => base85_digit(*c, z85)?,
+                None => 84,
+            };
+            value = value * 85 + digit as u64;
+        }
+        let value = u32::try_from(value)
+            .map_err(|_| anyhow::anyhow!("invalid base85 input, group out of range"))?;
+        decoded.extend_from_slice(&value.to_be_bytes()[..group.len() - 1]);
+    }
+    Ok(decoded)
+}
+
+fn base85_digit(c: u8, z85: bool) -> anyhow::Result<u8> {
+    let digit = match z85 {
+        true => Z85_ALPHABET.iter().position(|a| *a == c).map(|d| d as u8),
+        false => c.checked_sub(ASCII85_OFFSET).filter(|d| *d < 85),
+    };
+    digit.ok_or_else(|| anyhow::anyhow!("invalid base85 character {:?}", c as char))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(data: &[u8], codec: Codec) -> anyhow::Result<String> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("data");
+        std::fs::write(&path, data)?;
+        let mut encoded = Vec::new();
+        process_codec_encode(path.to_str().unwrap(), &mut encoded, codec)?;
+
+        std::fs::write(&path, &encoded)?;
+        let mut decoded = Vec::new();
+        process_codec_decode(path.to_str().unwrap(), &mut decoded, codec)?;
+        assert_eq!(decoded, data, "{}", codec);
+        Ok(String::from_utf8(encoded)?)
+    }
+
+    #[test]
+    fn test_known_vectors() -> anyhow::Result<()> {
+        let cases = [
+            (Codec::Hex, &b"\x00\xffhi"[..], "00ff6869"),
+            (Codec::Base32, b"foobar", "MZXW6YTBOI======"),
+            (Codec::Base32Hex, b"foobar", "CPNMUOJ1E8======"),
+            (Codec::Base58, b"\x00\x00hello", "11Cn8eVZg"),
+            (Codec::Base85, b"hello", "BOu!rDZ"),
+            (Codec::Base85, b"\0\0\0\0Man ", "z9jqo^"),
+            (
+                Codec::Z85,
+                b"\x86\x4f\xd2\x6f\xb5\x59\xf7\x5b",
+                "HelloWorld",
+            ),
+        ];
+        for (codec, data, expected) in cases {
+            assert_eq!(round_trip(data, codec)?, expected, "{}", codec);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_stream_round_trip() -> anyhow::Result<()> {
+        // several chunks for every codec, with a partial trailing group
+        let data = (0..100_000u32).map(|i| (i % 253) as u8).collect::<Vec<_>>();
+        for codec in [
+            Codec::Hex,
+            Codec::Base32,
+            Codec::Base32Hex,
+            Codec::Base85,
+            Codec::Z85,
+        ] {
+            round_trip(&data, codec)?;
+        }
+        round_trip(&data[..1000], Codec::Base58)?;
+        assert!(round_trip(&data[..99_999], Codec::Z85).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_lenient_decode() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("data");
+        let decode = |content: &str, codec| -> anyhow::Result<Vec<u8>> {
+            std::fs::write(&path, content)?;
+            let mut decoded = Vec::new();
+            process_codec_decode(path.to_str().unwrap(), &mut decoded, codec)?;
+            Ok(decoded)
+        };
+        assert_eq!(decode("mzxw 6ytb\noi\n", Codec::Base32)?, b"foobar");
+        assert_eq!(decode("00FF6869\n", Codec::Hex)?, b"\x00\xffhi");
+        assert!(decode("0", Codec::Hex).is_err());
+        assert!(decode("9jzqo", Codec::Base85).is_err());
+        assert_eq!(decode("<~BOu!rDZ~>\n", Codec::Base85)?, b"hello");
+        assert_eq!(decode("< ~BOu!\nrDZ ~ >", Codec::Base85)?, b"hello");
+        assert!(decode("<~BOu!rDZ~", Codec::Base85).is_err());
+        assert!(decode("<~BOu!rDZ~>BO", Codec::Base85).is_err());
+        assert!(decode("BO~u!rDZ", Codec::Base85).is_err());
+        assert!(decode("0OIl", Codec::Base58).is_err());
+        Ok(())
+    }
+}
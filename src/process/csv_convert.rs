@@ -2,10 +2,22 @@ use std::fs;
 
 use anyhow::Result;
 use csv::Reader;
+use serde::Serialize;
 use serde_json::Value;
 
 use crate::cli::OutputFormat;
 
+impl OutputFormat {
+    /// Pretty printed JSON or YAML
+    pub fn serialize<T: Serialize>(self, value: &T) -> Result<String> {
+        let content = match self {
+            OutputFormat::Json => serde_json::to_string_pretty(value)?,
+            OutputFormat::Yaml => serde_yaml::to_string(value)?,
+        };
+        Ok(content)
+    }
+}
+
 pub fn process_csv(input: &str, output: String, format: OutputFormat) -> Result<()> {
     let mut reader = Reader::from_path(input)?;
     let mut ret = Vec::with_capacity(128);
@@ -16,11 +28,7 @@ pub fn process_csv(input: &str, output: String, format: OutputFormat) -> Result<
         ret.push(value);
     }
 
-    let content = match format {
-        OutputFormat::Json => serde_json::to_string_pretty(&ret)?,
-        OutputFormat::Yaml => serde_yaml::to_string(&ret)?,
-    };
-    fs::write(output, content)?;
+    fs::write(output, format.serialize(&ret)?)?;
 
     Ok(())
 }
@@ -0,0 +1,171 @@
+use hkdf::Hkdf;
+use sha2::Sha256;
+
+use crate::process::key_file::read_key_file;
+use crate::process::text::{decode_master_key, Ed25519Signer};
+use crate::{DeriveKeyType, Kdf, KeyFormat};
+
+const KEY_LEN: usize = 32;
+
+/// Derive `len` bytes from the master secret, `context` should name the key's purpose
+pub fn derive_key_bytes(
+    master: &[u8],
+    context: &str,
+    kdf: Kdf,
+    len: usize,
+) -> anyhow::Result<Vec<u8>> {
+    if master.is_empty() {
+        anyhow::bail!("master key can't be empty");
+    }
+    if context.is_empty() {
+        anyhow::bail!("context can't be empty");
+    }
+    let mut okm = vec![0u8; len];
+    match kdf {
+        Kdf::Blake3 => {
+            let mut hasher = blake3::Hasher::new_derive_key(context);
+            hasher.update(master);
+            hasher.finalize_xof().fill(&mut okm);
+        }
+        Kdf::HkdfSha256 => {
+            Hkdf::<Sha256>::new(None, master)
+                .expand(context.as_bytes(), &mut okm)
+                .map_err(|e| anyhow::anyhow!("hkdf: {}", e))?;
+        }
+    }
+    Ok(okm)
+}
+
+/// Derive a key file for `text sign`/`text encrypt` from a master key file (or `vault:name`).
+/// The master key is decoded first, so `text generate` output and its raw bytes agree.
+/// Keys are hex encoded unless another ed25519 key format is asked for.
+pub fn process_text_derive(
+    master: &str,
+    context: &str,
+    kdf: Kdf,
+    key_type: DeriveKeyType,
+    key_format: KeyFormat,
+) -> anyhow::Result<Vec<u8>> {
+    let master = decode_master_key(&read_key_file(master)?)?;
+    let key = match key_type {
+        DeriveKeyType::Blake3 | DeriveKeyType::ChaCha20Poly1305 => {
+            let key = derive_key_bytes(&master, context, kdf, KEY_LEN)?;
+            hex::encode(key).into_bytes()
+        }
+        DeriveKeyType::Ed25519 => {
+            let seed = derive_key_bytes(&master, context, kdf, KEY_LEN)?;
+            match key_format {
+                KeyFormat::Raw => hex::encode(seed).into_bytes(),
+                _ => Ed25519Signer::try_new(&seed)?.export(key_format)?.remove(0),
+            }
+        }
+    };
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        process_text_decrypt, process_text_encrypt, process_text_sign, SignatureEncoding,
+        TextEncoding, TextSignFormat,
+    };
+
+    #[test]
+    fn test_derive_key_bytes() -> anyhow::Result<()> {
+        let okm = derive_key_bytes(b"master", "service-a/encrypt", Kdf::HkdfSha256, 44)?;
+        assert_eq!(okm.len(), 44);
+        let blake3 = derive_key_bytes(b"master", "service-a/encrypt", Kdf::Blake3, 44)?;
+        assert_ne!(okm, blake3);
+
+        let a = derive_key_bytes(b"master", "service-a/encrypt", Kdf::Blake3, 32)?;
+        let b = derive_key_bytes(b"master", "service-a/encrypt", Kdf::Blake3, 32)?;
+        let c = derive_key_bytes(b"master", "service-b/encrypt", Kdf::Blake3, 32)?;
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(
+            a,
+            blake3::derive_key("service-a/encrypt", b"master").to_vec()
+        );
+        assert!(derive_key_bytes(b"", "ctx", Kdf::Blake3, 32).is_err());
+        assert!(derive_key_bytes(b"master", "", Kdf::Blake3, 32).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_master_key_is_normalized() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let derive = |content: &[u8]| {
+            let path = dir.path().join("master");
+            std::fs::write(&path, content)?;
+            let path = path.to_str().unwrap();
+            process_text_derive(
+                path,
+                "test/sign",
+                Kdf::Blake3,
+                DeriveKeyType::Blake3,
+                KeyFormat::Raw,
+            )
+        };
+        assert_eq!(derive(b"key\n")?, derive(b"key")?);
+        assert_eq!(derive(b"key\r\n")?, derive(b"key")?);
+
+        let raw = [7u8; 32];
+        let hex = hex::encode(raw);
+        assert_eq!(derive(hex.as_bytes())?, derive(&raw)?);
+        assert_eq!(derive(format!("{}\n", hex).as_bytes())?, derive(&raw)?);
+        assert_ne!(derive(b"key")?, derive(&raw)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_derived_keys_load() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let input = dir.path().join("input.txt");
+        std::fs::write(&input, b"hello")?;
+        let input = input.to_str().unwrap();
+        let master = "fixtures/blake3.txt";
+        for kdf in [Kdf::Blake3, Kdf::HkdfSha256] {
+            for (key_type, format) in [
+                (DeriveKeyType::Blake3, TextSignFormat::Blake3),
+                (DeriveKeyType::Ed25519, TextSignFormat::Ed25519),
+            ] {
+                let key = process_text_derive(master, "test/sign", kdf, key_type, KeyFormat::Raw)?;
+                let path = dir.path().join("key");
+                std::fs::write(&path, &key)?;
+                let path = path.to_str().unwrap();
+                let sign = |p| {
+                    process_text_sign(
+                        p,
+                        path,
+                        format,
+                        SignatureEncoding::Raw,
+                        TextEncoding::Base64Url,
+                        None,
+                    )
+                };
+                assert_eq!(sign(input)?, sign(input)?);
+            }
+
+            let key = process_text_derive(
+                master,
+                "test/encrypt",
+                kdf,
+                DeriveKeyType::ChaCha20Poly1305,
+                KeyFormat::Raw,
+            )?;
+            assert_eq!(key.len(), KEY_LEN * 2);
+            let path = dir.path().join("chacha");
+            std::fs::write(&path, &key)?;
+            let path = path.to_str().unwrap();
+            let encrypted = process_text_encrypt(input, path)?;
+            let ciphertext = dir.path().join("encrypted.txt");
+            std::fs::write(&ciphertext, &encrypted)?;
+            assert_eq!(
+                process_text_decrypt(ciphertext.to_str().unwrap(), path)?,
+                "hello"
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,342 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+use sha2::{Digest, Sha256, Sha512};
+use sha3::{Sha3_256, Sha3_512};
+use walkdir::WalkDir;
+use xxhash_rust::{xxh3::Xxh3, xxh64::Xxh64};
+
+use crate::utils::{escape_path, get_reader, unescape_path};
+use crate::HashAlgorithm;
+
+/// One `sha256sum` style line: `<hex digest>  <path>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HashEntry {
+    pub path: String,
+    pub hash: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Ok,
+    Failed,
+    Unreadable,
+}
+
+#[derive(Debug)]
+pub struct CheckResult {
+    pub path: String,
+    pub status: CheckStatus,
+}
+
+/// Results of one checksum list, plus how many lines couldn't be parsed
+#[derive(Debug)]
+pub struct CheckReport {
+    pub results: Vec<CheckResult>,
+    pub malformed: usize,
+}
+
+enum Hasher {
+    Blake3(Box<blake3::Hasher>),
+    Sha256(Sha256),
+    Sha512(Sha512),
+    Sha3_256(Sha3_256),
+    Sha3_512(Sha3_512),
+    Xxh64(Xxh64),
+    Xxh3(Box<Xxh3>),
+}
+
+impl Hasher {
+    fn new(algorithm: HashAlgorithm) -> Self {
+        match algorithm {
+            HashAlgorithm::Blake3 => Hasher::Blake3(Box::default()),
+            HashAlgorithm::Sha256 => Hasher::Sha256(Sha256::new()),
+            HashAlgorithm::Sha512 => Hasher::Sha512(Sha512::new()),
+            HashAlgorithm::Sha3_256 => Hasher::Sha3_256(Sha3_256::new()),
+            HashAlgorithm::Sha3_512 => Hasher::Sha3_512(Sha3_512::new()),
+            HashAlgorithm::Xxh64 => Hasher::Xxh64(Xxh64::new(0)),
+            HashAlgorithm::Xxh3 => Hasher::Xxh3(Box::default()),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Blake3(h) => {
+                h.update(data);
+            }
+            Hasher::Sha256(h) => h.update(data),
+            Hasher::Sha512(h) => h.update(data),
+            Hasher::Sha3_256(h) => h.update(data),
+            Hasher::Sha3_512(h) => h.update(data),
+            Hasher::Xxh64(h) => h.update(data),
+            Hasher::Xxh3(h) => h.update(data),
+        }
+    }
+
+    // length of the hex digest
+    fn hex_len(algorithm: HashAlgorithm) -> usize {
+        match algorithm {
+            HashAlgorithm::Blake3 | HashAlgorithm::Sha256 | HashAlgorithm::Sha3_256 => 64,
+            HashAlgorithm::Sha512 | HashAlgorithm::Sha3_512 => 128,
+            HashAlgorithm::Xxh64 | HashAlgorithm::Xxh3 => 16,
+        }
+    }
+
+    // xxhash digests are printed big-endian, same as xxhsum
+    fn finalize(self) -> Vec<u8> {
+        match self {
+            Hasher::Blake3(h) => h.finalize().as_bytes().to_vec(),
+            Hasher::Sha256(h) => h.finalize().to_vec(),
+            Hasher::Sha512(h) => h.finalize().to_vec(),
+            Hasher::Sha3_256(h) => h.finalize().to_vec(),
+            Hasher::Sha3_512(h) => h.finalize().to_vec(),
+            Hasher::Xxh64(h) => h.digest().to_be_bytes().to_vec(),
+            Hasher::Xxh3(h) => h.digest().to_be_bytes().to_vec(),
+        }
+    }
+}
+
+impl fmt::Display for HashEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match escape_path(&self.path) {
+            Some(path) => write!(f, "\\{}  {}", self.hash, path),
+            None => write!(f, "{}  {}", self.hash, self.path),
+        }
+    }
+}
+
+impl CheckResult {
+    pub fn is_ok(&self) -> bool {
+        self.status == CheckStatus::Ok
+    }
+}
+
+impl fmt::Display for CheckResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = match self.status {
+            CheckStatus::Ok => "OK",
+            CheckStatus::Failed => "FAILED",
+            CheckStatus::Unreadable => "FAILED open or read",
+        };
+        write!(f, "{}: {}", self.path, status)
+    }
+}
+
+/// Hash a reader with the given algorithm and return the lowercase hex digest
+pub fn hash_reader(mut reader: impl Read, algorithm: HashAlgorithm) -> anyhow::Result<String> {
+    let mut hasher = Hasher::new(algorithm);
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Hash each input ("-" is stdin). Directories are walked in sorted order when `recursive` is set.
+pub fn process_hash(
+    inputs: &[String],
+    algorithm: HashAlgorithm,
+    recursive: bool,
+) -> anyhow::Result<Vec<HashEntry>> {
+    let mut entries = Vec::new();
+    for input in inputs {
+        if Path::new(input).is_dir() {
+            if !recursive {
+                anyhow::bail!(
+                    "{}: is a directory, use --recursive to hash its files",
+                    input
+                );
+            }
+            for entry in WalkDir::new(input).sort_by_file_name() {
+                let entry = entry?;
+                if !entry.file_type().is_file() {
+                    continue;
+                }
+                let path = entry.path();
+                entries.push(HashEntry {
+                    path: path.display().to_string(),
+                    hash: hash_reader(File::open(path)?, algorithm)?,
+                });
+            }
+        } else {
+            let reader = get_reader(input)?;
+            entries.push(HashEntry {
+                path: input.clone(),
+                hash: hash_reader(reader, algorithm)?,
+            });
+        }
+    }
+    Ok(entries)
+}
+
+/// Verify a checksum list in `sha256sum` format (`-` reads it from stdin).
+/// Paths are relative to the current directory, like `sha256sum --check`.
+/// Malformed lines are counted and skipped.
+pub fn process_hash_check(input: &str, algorithm: HashAlgorithm) -> anyhow::Result<CheckReport> {
+    let reader = BufReader::new(get_reader(input)?);
+    let mut results = Vec::new();
+    let mut malformed = 0;
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Ok(entry) = parse_check_line(&line, algorithm) else {
+            malformed += 1;
+            continue;
+        };
+        let status = match File::open(&entry.path) {
+            Ok(file) => match hash_reader(file, algorithm) {
+                Ok(hash) if hash.eq_ignore_ascii_case(&entry.hash) => CheckStatus::Ok,
+                Ok(_) => CheckStatus::Failed,
+                Err(_) => CheckStatus::Unreadable,
+            },
+            Err(_) => CheckStatus::Unreadable,
+        };
+        results.push(CheckResult {
+            path: entry.path,
+            status,
+        });
+    }
+    if results.is_empty() {
+        anyhow::bail!("{}: no properly formatted checksum lines found", input);
+    }
+    Ok(CheckReport { results, malformed })
+}
+
+// `<hash>  <path>` (text mode) or `<hash> *<path>` (binary mode), with a leading `\`
+// when the path is escaped
+fn parse_check_line(line: &str, algorithm: HashAlgorithm) -> anyhow::Result<HashEntry> {
+    let (escaped, rest) = match line.strip_prefix('\\') {
+        Some(rest) => (true, rest),
+        None => (false, line),
+    };
+    let (hash, path) = rest
+        .split_once(' ')
+        .ok_or_else(|| anyhow::anyhow!("invalid checksum line: {}", line))?;
+    let path = path
+        .strip_prefix(' ')
+        .or_else(|| path.strip_prefix('*'))
+        .ok_or_else(|| anyhow::anyhow!("invalid checksum line: {}", line))?;
+    if hash.len() != Hasher::hex_len(algorithm) || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        anyhow::bail!("invalid {} checksum line: {}", algorithm, line);
+    }
+    let path = match escaped {
+        true => unescape_path(path)?,
+        false => path.to_string(),
+    };
+    Ok(HashEntry {
+        path,
+        hash: hash.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_reader_known_digests() -> anyhow::Result<()> {
+        let cases = [
+            (
+                HashAlgorithm::Sha256,
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            ),
+            (
+                HashAlgorithm::Sha3_256,
+                "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532",
+            ),
+            (
+                HashAlgorithm::Blake3,
+                "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85",
+            ),
+            (HashAlgorithm::Xxh64, "44bc2cf5ad770999"),
+            (HashAlgorithm::Xxh3, "78af5f94892f3950"),
+        ];
+        for (algorithm, expected) in cases {
+            assert_eq!(
+                hash_reader(&b"abc"[..], algorithm)?,
+                expected,
+                "{}",
+                algorithm
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_hash_and_check() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::create_dir(dir.path().join("sub"))?;
+        std::fs::write(dir.path().join("a.txt"), b"hello")?;
+        std::fs::write(dir.path().join("sub/b.txt"), b"world")?;
+        let input = dir.path().display().to_string();
+
+        assert!(process_hash(std::slice::from_ref(&input), HashAlgorithm::Sha256, false).is_err());
+        let entries = process_hash(&[input], HashAlgorithm::Sha256, true)?;
+        assert_eq!(entries.len(), 2);
+        assert!(entries[0].path.ends_with("a.txt"));
+        assert_eq!(
+            entries[0].hash,
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+
+        let list = dir.path().join("SHA256SUMS");
+        let mut content = entries
+            .iter()
+            .map(|e| format!("{}\n", e))
+            .collect::<String>();
+        content.push_str(&format!(
+            "{} *{}/gone.txt\n",
+            "00".repeat(32),
+            dir.path().display()
+        ));
+        content.push_str("not a checksum line\n");
+        content.push_str(&format!(
+            "{}  {}/a.txt\n",
+            "00".repeat(16),
+            dir.path().display()
+        ));
+        std::fs::write(&list, content)?;
+        std::fs::write(dir.path().join("sub/b.txt"), b"changed")?;
+
+        let report = process_hash_check(&list.display().to_string(), HashAlgorithm::Sha256)?;
+        assert_eq!(report.malformed, 2);
+        let statuses = report.results.iter().map(|r| r.status).collect::<Vec<_>>();
+        assert_eq!(
+            statuses,
+            [
+                CheckStatus::Ok,
+                CheckStatus::Failed,
+                CheckStatus::Unreadable
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_check_line() {
+        let algorithm = HashAlgorithm::Xxh64;
+        let entry = parse_check_line("44bc2cf5ad770999 *file name.bin", algorithm).unwrap();
+        assert_eq!(entry.path, "file name.bin");
+        assert_eq!(entry.hash, "44bc2cf5ad770999");
+        assert!(parse_check_line("44bc2cf5ad770999 file", algorithm).is_err());
+        assert!(parse_check_line("xyzc2cf5ad770999  file", algorithm).is_err());
+        assert!(parse_check_line("abcd  file", algorithm).is_err());
+        assert!(parse_check_line("44bc2cf5ad770999  file", HashAlgorithm::Sha256).is_err());
+
+        let entry = HashEntry {
+            path: "dir\\new\nline".to_string(),
+            hash: "44bc2cf5ad770999".to_string(),
+        };
+        let line = entry.to_string();
+        assert_eq!(line, "\\44bc2cf5ad770999  dir\\\\new\\nline");
+        assert_eq!(parse_check_line(&line, algorithm).unwrap(), entry);
+    }
+}
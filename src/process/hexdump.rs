@@ -0,0 +1,169 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+
+use crate::utils::{get_reader, read_full};
+
+const BYTES_PER_LINE: usize = 16;
+
+// xxd's colors: NUL white, printable green, whitespace yellow, anything else red
+const COLOR_NUL: &str = "\x1b[1;37m";
+const COLOR_PRINTABLE: &str = "\x1b[1;32m";
+const COLOR_WHITESPACE: &str = "\x1b[1;33m";
+const COLOR_OTHER: &str = "\x1b[1;31m";
+const COLOR_RESET: &str = "\x1b[0m";
+
+/// Write an xxd style dump: offset, hex bytes in groups of `group` (0 for no spaces)
+/// and the ASCII column. Offsets are those of the input, so they start at `skip`.
+pub fn process_hexdump(
+    input: &str,
+    writer: &mut dyn Write,
+    skip: u64,
+    length: Option<u64>,
+    group: usize,
+    color: bool,
+) -> anyhow::Result<()> {
+    let mut reader = get_reader(input)?;
+    // stdin can't seek, read past the skipped bytes instead
+    let skipped = io::copy(&mut reader.by_ref().take(skip), &mut io::sink())?;
+    if skipped < skip {
+        anyhow::bail!("can't skip {} bytes, the input only has {}", skip, skipped);
+    }
+    let mut reader = reader.take(length.unwrap_or(u64::MAX));
+
+    let separators = match group {
+        0 => 0,
+        _ => (BYTES_PER_LINE - 1) / group,
+    };
+    let hex_width = BYTES_PER_LINE * 2 + separators;
+    let mut offset = skip;
+    let mut buf = [0u8; BYTES_PER_LINE];
+    loop {
+        let n = read_full(&mut reader, &mut buf)?;
+        if n == 0 {
+            return Ok(());
+        }
+        let mut line = format!("{:08x}: ", offset);
+        let mut width = 0;
+        for (i, b) in buf[..n].iter().enumerate() {
+            push_colored(&mut line, *b, &format!("{:02x}", b), color);
+            width += 2;
+            if group > 0 && (i + 1) % group == 0 && i + 1 < BYTES_PER_LINE {
+                line.push(' ');
+                width += 1;
+            }
+        }
+        line.push_str(&" ".repeat(hex_width - width + 2));
+        for b in &buf[..n] {
+            let c = match b {
+                0x20..=0x7e => *b as char,
+                _ => '.',
+            };
+            push_colored(&mut line, *b, &c.to_string(), color);
+        }
+        writeln!(writer, "{}", line)?;
+        offset += n as u64;
+    }
+}
+
+/// Turn a dump back into bytes. Gaps between offsets are filled with zeros like `xxd -r`.
+pub fn process_hexdump_reverse(input: &str, writer: &mut dyn Write) -> anyhow::Result<()> {
+    let reader = BufReader::new(get_reader(input)?);
+    let mut position = 0u64;
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (offset, bytes) =
+            parse_dump_line(&line).map_err(|e| anyhow::anyhow!("line {}: {}", number + 1, e))?;
+        if offset < position {
+            anyhow::bail!("line {}: offset {:x} goes backwards", number + 1, offset);
+        }
+        io::copy(&mut io::repeat(0).take(offset - position), writer)?;
+        writer.write_all(&bytes)?;
+        position = offset + bytes.len() as u64;
+    }
+    Ok(())
+}
+
+// `<offset>: <hex groups>  <ascii>`, the hex part ends at the first double space
+fn parse_dump_line(line: &str) -> anyhow::Result<(u64, Vec<u8>)> {
+    let (offset, rest) = line
+        .split_once(':')
+        .ok_or_else(|| anyhow::anyhow!("missing offset"))?;
+    let offset = u64::from_str_radix(offset.trim(), 16)?;
+    let rest = rest.strip_prefix(' ').unwrap_or(rest);
+    let hex = rest.split("  ").next().unwrap_or_default();
+    let digits = hex.chars().filter(|c| *c != ' ').collect::<String>();
+    let bytes = hex::decode(&digits)?;
+    if bytes.len() > BYTES_PER_LINE {
+        anyhow::bail!("more than {} bytes on a line", BYTES_PER_LINE);
+    }
+    Ok((offset, bytes))
+}
+
+fn push_colored(line: &mut String, b: u8, text: &str, color: bool) {
+    if !color {
+        line.push_str(text);
+        return;
+    }
+    let code = match b {
+        0 => COLOR_NUL,
+        b'\t' | b'\n' | b'\r' | b' ' => COLOR_WHITESPACE,
+        0x21..=0x7e => COLOR_PRINTABLE,
+        _ => COLOR_OTHER,
+    };
+    line.push_str(code);
+    line.push_str(text);
+    line.push_str(COLOR_RESET);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dump(data: &[u8], skip: u64, length: Option<u64>, group: usize) -> anyhow::Result<String> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("data");
+        std::fs::write(&path, data)?;
+        let mut out = Vec::new();
+        process_hexdump(path.to_str().unwrap(), &mut out, skip, length, group, false)?;
+        Ok(String::from_utf8(out)?)
+    }
+
+    #[test]
+    fn test_hexdump_matches_xxd() -> anyhow::Result<()> {
+        let output = dump(b"hello world, this is rcli\n", 0, None, 2)?;
+        assert_eq!(
+            output,
+            "00000000: 6865 6c6c 6f20 776f 726c 642c 2074 6869  hello world, thi\n\
+             00000010: 7320 6973 2072 636c 690a                 s is rcli.\n"
+        );
+        let output = dump(b"hello world, this is rcli\n", 18, Some(4), 1)?;
+        assert_eq!(
+            output,
+            "00000012: 69 73 20 72                                      is r\n"
+        );
+        assert!(dump(b"short", 10, None, 2).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_hexdump_reverse() -> anyhow::Result<()> {
+        let data = (0..=255u8)
+            .chain(b"  trailing  ".iter().copied())
+            .collect::<Vec<_>>();
+        for group in [0, 1, 2, 4, 8] {
+            let dir = tempfile::tempdir()?;
+            let path = dir.path().join("dump.txt");
+            std::fs::write(&path, dump(&data, 0, None, group)?)?;
+            let mut out = Vec::new();
+            process_hexdump_reverse(path.to_str().unwrap(), &mut out)?;
+            assert_eq!(out, data, "group {}", group);
+        }
+
+        let (offset, bytes) = parse_dump_line("00000020: 4142 4344  ABCD")?;
+        assert_eq!((offset, bytes), (0x20, b"ABCD".to_vec()));
+        assert!(parse_dump_line("not a dump").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,366 @@
+use std::fmt;
+use std::io::Write;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use rand::rngs::OsRng;
+use rand::RngCore;
+
+use crate::IdKind;
+
+// Crockford's base32, no I, L, O or U
+const ULID_ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+const ULID_LEN: usize = 26;
+const ULID_RANDOM_BITS: u32 = 80;
+// 64 url safe characters, a random byte masked to 6 bits picks one without bias
+const NANOID_ALPHABET: &[u8; 64] =
+    b"_-0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const NANOID_DEFAULT_LEN: usize = 21;
+// Twitter's snowflake layout: 41 bits of milliseconds since its epoch, 10 bit node, 12 bit sequence
+pub const SNOWFLAKE_EPOCH_MS: u64 = 1_288_834_974_657;
+const SNOWFLAKE_NODE_BITS: u32 = 10;
+const SNOWFLAKE_SEQUENCE_BITS: u32 = 12;
+pub const SNOWFLAKE_MAX_NODE: u16 = (1 << SNOWFLAKE_NODE_BITS) - 1;
+
+/// What `process_id_parse` found in an id
+#[derive(Debug)]
+pub struct IdInfo {
+    pub kind: IdKind,
+    pub timestamp: Option<DateTime<Utc>>,
+    /// UUID version
+    pub version: Option<u8>,
+    pub node: Option<u16>,
+    pub sequence: Option<u16>,
+    /// Only the characters match, e.g. a nanoid, which has no structure to check
+    pub guess: bool,
+}
+
+impl fmt::Display for IdInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.guess {
+            true => write!(f, "kind: possible {}", self.kind)?,
+            false => write!(f, "kind: {}", self.kind)?,
+        }
+        if let Some(version) = self.version {
+            write!(f, "\nversion: {}", version)?;
+        }
+        match self.timestamp {
+            Some(timestamp) => write!(
+                f,
+                "\ntimestamp: {}",
+                timestamp.to_rfc3339_opts(SecondsFormat::Millis, true)
+            )?,
+            None => write!(f, "\ntimestamp: none, the id is random")?,
+        }
+        if let Some(node) = self.node {
+            write!(f, "\nnode: {}", node)?;
+        }
+        if let Some(sequence) = self.sequence {
+            write!(f, "\nsequence: {}", sequence)?;
+        }
+        Ok(())
+    }
+}
+
+/// Write `count` ids to `writer`, one per line, as they are generated. ULIDs and snowflakes
+/// made in the same millisecond still sort in generation order, `length` is the nanoid length
+/// and `node` the snowflake node id.
+pub fn process_id_generate(
+    kind: IdKind,
+    count: usize,
+    length: usize,
+    node: u16,
+    writer: &mut dyn Write,
+) -> anyhow::Result<()> {
+    if node > SNOWFLAKE_MAX_NODE {
+        anyhow::bail!("snowflake node must be at most {}", SNOWFLAKE_MAX_NODE);
+    }
+    if length == 0 {
+        anyhow::bail!("nanoid length can't be zero");
+    }
+    // (millisecond, last value) for monotonic ulids and snowflake sequences
+    let mut last = (0u64, 0u128);
+    for _ in 0..count {
+        let id = match kind {
+            IdKind::UuidV4 => {
+                let mut bytes = [0u8; 16];
+                OsRng.fill_bytes(&mut bytes);
+                format_uuid(bytes, 4)
+            }
+            IdKind::UuidV7 => {
+                let mut bytes = [0u8; 16];
+                OsRng.fill_bytes(&mut bytes[6..]);
+                bytes[..6].copy_from_slice(&now_ms()?.to_be_bytes()[2..]);
+                format_uuid(bytes, 7)
+            }
+            IdKind::Ulid => {
+                let ms = now_ms()?;
+                let random = match last {
+                    (last_ms, last_random) if last_ms == ms => last_random
+                        .checked_add(1)
+                        .filter(|r| *r >> ULID_RANDOM_BITS == 0)
+                        .ok_or_else(|| anyhow::anyhow!("ulid random part overflowed"))?,
+                    _ => {
+                        let mut bytes = [0u8; 16];
+                        OsRng.fill_bytes(&mut bytes[6..]);
+                        u128::from_be_bytes(bytes)
+                    }
+                };
+                last = (ms, random);
+                encode_ulid(((ms as u128) << ULID_RANDOM_BITS) | random)
+            }
+            IdKind::NanoId => {
+                let mut bytes = vec![0u8; length];
+                OsRng.fill_bytes(&mut bytes);
+                bytes
+                    .iter()
+                    .map(|b| NANOID_ALPHABET[(b & 63) as usize] as char)
+                    .collect()
+            }
+            IdKind::Snowflake => {
+                let (ms, sequence) = next_snowflake_tick(last)?;
+                last = (ms, sequence as u128);
+                let id = ((ms - SNOWFLAKE_EPOCH_MS)
+                    << (SNOWFLAKE_NODE_BITS + SNOWFLAKE_SEQUENCE_BITS))
+                    | ((node as u64) << SNOWFLAKE_SEQUENCE_BITS)
+                    | sequence;
+                id.to_string()
+            }
+        };
+        writeln!(writer, "{}", id)?;
+    }
+    Ok(())
+}
+
+/// Tell what kind of id this is and decode the timestamp of UUID v7, ULID and snowflake ids
+pub fn process_id_parse(id: &str) -> anyhow::Result<IdInfo> {
+    let id = id.trim();
+    let info = |kind, timestamp| IdInfo {
+        kind,
+        timestamp,
+        version: None,
+        node: None,
+        sequence: None,
+        guess: false,
+    };
+    if let Some(bytes) = parse_uuid(id) {
+        let version = bytes[6] >> 4;
+        let (kind, timestamp) = match version {
+            7 => {
+                let mut ms = [0u8; 8];
+                ms[2..].copy_from_slice(&bytes[..6]);
+                (IdKind::UuidV7, Some(timestamp(u64::from_be_bytes(ms))?))
+            }
+            4 => (IdKind::UuidV4, None),
+            _ => anyhow::bail!("uuid version {} isn't supported", version),
+        };
+        return Ok(IdInfo {
+            version: Some(version),
+            ..info(kind, timestamp)
+        });
+    }
+    if let Some(value) = decode_ulid(id) {
+        let ms = (value >> ULID_RANDOM_BITS) as u64;
+        return Ok(info(IdKind::Ulid, Some(timestamp(ms)?)));
+    }
+    if let Some(value) = id
+        .parse::<u64>()
+        .ok()
+        .filter(|v| is_plausible_snowflake(*v))
+    {
+        let ms = (value >> (SNOWFLAKE_NODE_BITS + SNOWFLAKE_SEQUENCE_BITS)) + SNOWFLAKE_EPOCH_MS;
+        return Ok(IdInfo {
+            node: Some(((value >> SNOWFLAKE_SEQUENCE_BITS) as u16) & SNOWFLAKE_MAX_NODE),
+            sequence: Some((value & ((1 << SNOWFLAKE_SEQUENCE_BITS) - 1)) as u16),
+            ..info(IdKind::Snowflake, Some(timestamp(ms)?))
+        });
+    }
+    // nanoids are random, the default length is the only hint
+    if id.len() == NANOID_DEFAULT_LEN && id.bytes().all(|b| NANOID_ALPHABET.contains(&b)) {
+        return Ok(IdInfo {
+            guess: true,
+            ..info(IdKind::NanoId, None)
+        });
+    }
+    anyhow::bail!("unrecognized id, expected a uuid, ulid, snowflake or nanoid")
+}
+
+// made after the snowflake epoch and not in the future, so small numbers like `42` don't count
+fn is_plausible_snowflake(value: u64) -> bool {
+    let offset = value >> (SNOWFLAKE_NODE_BITS + SNOWFLAKE_SEQUENCE_BITS);
+    offset > 0 && now_ms().is_ok_and(|now| SNOWFLAKE_EPOCH_MS + offset <= now)
+}
+
+// the next (millisecond, sequence), waiting for the clock when a millisecond's
+// 4096 sequence numbers are used up
+fn next_snowflake_tick((last_ms, last_sequence): (u64, u128)) -> anyhow::Result<(u64, u64)> {
+    loop {
+        let ms = now_ms()?.max(last_ms);
+        if ms != last_ms {
+            return Ok((ms, 0));
+        }
+        let sequence = last_sequence as u64 + 1;
+        if sequence < 1 << SNOWFLAKE_SEQUENCE_BITS {
+            return Ok((ms, sequence));
+        }
+        std::thread::sleep(Duration::from_micros(100));
+    }
+}
+
+fn now_ms() -> anyhow::Result<u64> {
+    Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as u64)
+}
+
+fn timestamp(ms: u64) -> anyhow::Result<DateTime<Utc>> {
+    i64::try_from(ms)
+        .ok()
+        .and_then(DateTime::from_timestamp_millis)
+        .ok_or_else(|| anyhow::anyhow!("timestamp out of range"))
+}
+
+fn format_uuid(mut bytes: [u8; 16], version: u8) -> String {
+    bytes[6] = (bytes[6] & 0x0f) | (version << 4);
+    // RFC 9562 variant
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex = hex::encode(bytes);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+fn parse_uuid(id: &str) -> Option<[u8; 16]> {
+    let dashes = [8, 13, 18, 23];
+    if id.len() != 36 || dashes.iter().any(|i| id.as_bytes()[*i] != b'-') {
+        return None;
+    }
+    let mut bytes = [0u8; 16];
+    hex::decode_to_slice(id.replace('-', ""), &mut bytes).ok()?;
+    Some(bytes)
+}
+
+// 26 characters, the first one only carries 3 bits
+fn encode_ulid(value: u128) -> String {
+    (0..ULID_LEN)
+        .map(|i| {
+            let shift = 5 * (ULID_LEN - 1 - i);
+            ULID_ALPHABET[((value >> shift) & 31) as usize] as char
+        })
+        .collect()
+}
+
+fn decode_ulid(id: &str) -> Option<u128> {
+    if id.len() != ULID_LEN || id.as_bytes()[0] > b'7' {
+        return None;
+    }
+    id.bytes().try_fold(0u128, |value, c| {
+        // Crockford decoding is case insensitive and forgiving about look-alikes
+        let c = match c.to_ascii_uppercase() {
+            b'O' => b'0',
+            b'I' | b'L' => b'1',
+            c => c,
+        };
+        let digit = ULID_ALPHABET.iter().position(|a| *a == c)?;
+        Some((value << 5) | digit as u128)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn generate(
+        kind: IdKind,
+        count: usize,
+        length: usize,
+        node: u16,
+    ) -> anyhow::Result<Vec<String>> {
+        let mut buf = Vec::new();
+        process_id_generate(kind, count, length, node, &mut buf)?;
+        Ok(String::from_utf8(buf)?.lines().map(String::from).collect())
+    }
+
+    #[test]
+    fn test_generate_uuids() -> anyhow::Result<()> {
+        let ids = generate(IdKind::UuidV4, 3, 21, 0)?;
+        assert_eq!(ids.len(), 3);
+        assert_ne!(ids[0], ids[1]);
+        for id in &ids {
+            assert_eq!(id.len(), 36);
+            assert_eq!(&id[14..15], "4");
+            assert!(matches!(&id[19..20], "8" | "9" | "a" | "b"));
+            assert!(process_id_parse(id)?.timestamp.is_none());
+        }
+
+        let before = Utc::now().timestamp_millis();
+        let id = generate(IdKind::UuidV7, 1, 21, 0)?.remove(0);
+        let info = process_id_parse(&id)?;
+        assert!(matches!(info.kind, IdKind::UuidV7));
+        assert_eq!(info.version, Some(7));
+        let ms = info.timestamp.unwrap().timestamp_millis();
+        assert!(ms >= before && ms - before < 5000);
+        Ok(())
+    }
+
+    #[test]
+    fn test_ulid() -> anyhow::Result<()> {
+        // example from the ULID spec
+        let info = process_id_parse("01ARZ3NDEKTSV4RRFFQ69G5FAV")?;
+        assert!(matches!(info.kind, IdKind::Ulid));
+        assert_eq!(
+            info.timestamp.unwrap().timestamp_millis(),
+            1_469_922_850_259
+        );
+        assert_eq!(
+            decode_ulid("01arz3ndektsv4rrffq69g5fav"),
+            decode_ulid("01ARZ3NDEKTSV4RRFFQ69G5FAV")
+        );
+        assert!(decode_ulid("81ARZ3NDEKTSV4RRFFQ69G5FAV").is_none());
+
+        let ids = generate(IdKind::Ulid, 1000, 21, 0)?;
+        assert!(ids.windows(2).all(|w| w[0] < w[1]), "ulids must sort");
+        assert_eq!(encode_ulid(decode_ulid(&ids[0]).unwrap()), ids[0]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_snowflake_and_nanoid() -> anyhow::Result<()> {
+        let ids = generate(IdKind::Snowflake, 5000, 21, 42)?;
+        let values = ids
+            .iter()
+            .map(|id| id.parse::<u64>())
+            .collect::<Result<Vec<_>, _>>()?;
+        assert!(values.windows(2).all(|w| w[0] < w[1]));
+        let info = process_id_parse(&ids[0])?;
+        assert_eq!(info.node, Some(42));
+        let ms = info.timestamp.unwrap().timestamp_millis();
+        assert!((Utc::now().timestamp_millis() - ms) < 5000);
+        assert!(generate(IdKind::Snowflake, 1, 21, 1024).is_err());
+
+        let ids = generate(IdKind::NanoId, 2, 30, 0)?;
+        assert_eq!(ids[0].len(), 30);
+        assert_ne!(ids[0], ids[1]);
+        assert!(process_id_parse(&ids[0]).is_err());
+        let id = generate(IdKind::NanoId, 1, 21, 0)?.remove(0);
+        let info = process_id_parse(&id)?;
+        assert!(matches!(info.kind, IdKind::NanoId) && info.guess);
+        assert!(info.to_string().starts_with("kind: possible nanoid"));
+        assert!(process_id_parse("not an id!").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_rejects_implausible_ids() -> anyhow::Result<()> {
+        // too small to carry a timestamp, or dated in the future
+        for id in ["42", "4194303", &u64::MAX.to_string(), "hello", "abc_def"] {
+            assert!(process_id_parse(id).is_err(), "{} is not an id", id);
+        }
+        let info = process_id_parse("1541815603606036480")?;
+        assert!(matches!(info.kind, IdKind::Snowflake) && !info.guess);
+        Ok(())
+    }
+}
@@ -0,0 +1,332 @@
+use std::fmt;
+use std::io::Read;
+use std::path::Path;
+
+use flate2::read::GzDecoder;
+use percent_encoding::percent_decode;
+
+use crate::process::b64::{dearmor, decode_reader};
+use crate::process::jwt::decode_jwt_unverified;
+use crate::process::text::describe_key;
+use crate::utils::get_reader;
+use crate::Base64Format;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+// shorter base64 looking strings are only decoded when they turn into text
+const MIN_BINARY_BASE64_LEN: usize = 16;
+const PREVIEW_LEN: usize = 32;
+// stop inflating there, a small gzip bomb would otherwise fill the memory
+const MAX_INSPECT_LEN: usize = 8 * 1024 * 1024;
+
+/// One decoded layer, outermost first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layer {
+    pub encoding: String,
+    pub detail: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct Inspection {
+    pub layers: Vec<Layer>,
+    /// What's left after peeling off every layer
+    pub payload: Vec<u8>,
+}
+
+impl fmt::Display for Inspection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.layers.is_empty() {
+            writeln!(f, "no known encoding found")?;
+        }
+        for (i, layer) in self.layers.iter().enumerate() {
+            match &layer.detail {
+                Some(detail) => writeln!(f, "{}. {}: {}", i + 1, layer.encoding, detail)?,
+                None => writeln!(f, "{}. {}", i + 1, layer.encoding)?,
+            }
+        }
+        match text(&self.payload) {
+            Some(text) => match serde_json::from_str::<serde_json::Value>(text) {
+                Ok(json) if json.is_object() || json.is_array() => {
+                    let pretty = serde_json::to_string_pretty(&json).map_err(|_| fmt::Error)?;
+                    write!(f, "payload (json):\n{}", pretty)
+                }
+                _ => write!(f, "payload (text):\n{}", text),
+            },
+            None => {
+                let kind = infer::get(&self.payload)
+                    .map(|kind| format!(", {}", kind.mime_type()))
+                    .unwrap_or_default();
+                let end = self.payload.len().min(PREVIEW_LEN);
+                let more = if end < self.payload.len() { "..." } else { "" };
+                write!(
+                    f,
+                    "payload (binary, {} bytes{}):\n{}{}",
+                    self.payload.len(),
+                    kind,
+                    hex::encode(&self.payload[..end]),
+                    more
+                )
+            }
+        }
+    }
+}
+
+/// Work out what a string (or the content of a file, `-` for stdin) is, decoding
+/// up to `max_depth` layers of base64, hex, url encoding, gzip and PEM. JWTs and
+/// keys are described rather than decoded further.
+pub fn process_inspect(input: &str, max_depth: usize) -> anyhow::Result<Inspection> {
+    let mut data = Vec::new();
+    if input == "-" || Path::new(input).is_file() {
+        get_reader(input)?.read_to_end(&mut data)?;
+    } else {
+        data.extend_from_slice(input.as_bytes());
+    }
+    let mut layers = Vec::new();
+    while layers.len() < max_depth {
+        let Some((layer, decoded, last)) = peel(&data) else {
+            break;
+        };
+        layers.push(layer);
+        data = decoded;
+        if last {
+            break;
+        }
+    }
+    Ok(Inspection {
+        layers,
+        payload: data,
+    })
+}
+
+// the outermost layer, its content and whether to stop there
+fn peel(data: &[u8]) -> Option<(Layer, Vec<u8>, bool)> {
+    if data.starts_with(GZIP_MAGIC) {
+        let mut decoded = Vec::new();
+        GzDecoder::new(data)
+            .take(MAX_INSPECT_LEN as u64 + 1)
+            .read_to_end(&mut decoded)
+            .ok()?;
+        if decoded.len() > MAX_INSPECT_LEN {
+            decoded.truncate(MAX_INSPECT_LEN);
+            let detail = format!("truncated to the first {} bytes", MAX_INSPECT_LEN);
+            return Some((layer("gzip", Some(detail)), decoded, true));
+        }
+        return Some((layer("gzip", None), decoded, false));
+    }
+    let text = text(data)?.trim();
+    if looks_like_key(text) {
+        if let Ok(key) = describe_key(text.as_bytes(), None, false) {
+            let kind = if key.private { "private" } else { "public" };
+            let detail = format!("{} {} key ({} bits)", key.key_type, kind, key.bits);
+            return Some((layer("key", Some(detail)), data.to_vec(), true));
+        }
+    }
+    if text.starts_with("-----BEGIN ") {
+        let (label, decoded) = dearmor(text.as_bytes(), None).ok()?;
+        return Some((layer("pem", Some(label)), decoded, false));
+    }
+    if let Some(peeled) = jwt(text) {
+        return Some(peeled);
+    }
+    if has_percent_escapes(text) {
+        let decoded = percent_decode(text.as_bytes()).collect::<Vec<_>>();
+        return Some((layer("url-encoded", None), decoded, false));
+    }
+    if text.len() >= 2 && text.len() % 2 == 0 && text.bytes().all(|b| b.is_ascii_hexdigit()) {
+        let decoded = hex::decode(text).ok()?;
+        return Some((layer("hex", None), decoded, false));
+    }
+    base64(text)
+}
+
+fn jwt(text: &str) -> Option<(Layer, Vec<u8>, bool)> {
+    if text.split('.').count() != 3 {
+        return None;
+    }
+    let (header, claims) = decode_jwt_unverified(text).ok()?;
+    let mut detail = format!("alg {:?}", header.alg);
+    if let Some(kid) = header.kid {
+        detail.push_str(&format!(", kid {}", kid));
+    }
+    if let Some(exp) = claims["exp"]
+        .as_i64()
+        .and_then(|exp| chrono::DateTime::from_timestamp(exp, 0))
+    {
+        let state = if exp < chrono::Utc::now() {
+            "expired"
+        } else {
+            "expires"
+        };
+        detail.push_str(&format!(", {} {}", state, exp.to_rfc3339()));
+    }
+    let claims = serde_json::to_vec(&claims).ok()?;
+    Some((
+        layer("jwt (signature not verified)", Some(detail)),
+        claims,
+        true,
+    ))
+}
+
+fn base64(text: &str) -> Option<(Layer, Vec<u8>, bool)> {
+    // wrapped lines are fine, spaces mean words
+    let compact = text
+        .chars()
+        .filter(|c| !matches!(c, '\r' | '\n'))
+        .collect::<String>();
+    let valid_chars = compact
+        .bytes()
+        .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'/' | b'-' | b'_' | b'='));
+    if compact.len() < 4 || !valid_chars {
+        return None;
+    }
+    let mut decoded = Vec::new();
+    decode_reader(
+        &mut compact.as_bytes(),
+        &mut decoded,
+        Base64Format::Standard,
+        true,
+    )
+    .ok()?;
+    // plenty of words are valid base64, only trust short ones that decode to text
+    if compact.len() < MIN_BINARY_BASE64_LEN && text_of(&decoded).is_none() {
+        return None;
+    }
+    let alphabet = if compact.contains(['-', '_']) {
+        "url-safe"
+    } else {
+        "standard"
+    };
+    let padding = if compact.ends_with('=') {
+        "padded"
+    } else {
+        "unpadded"
+    };
+    let detail = format!("{}, {}", alphabet, padding);
+    Some((layer("base64", Some(detail)), decoded, false))
+}
+
+fn looks_like_key(text: &str) -> bool {
+    text.starts_with("-----BEGIN") && text.contains("KEY-----")
+        || text.starts_with("ssh-")
+        || text.starts_with("age1")
+        || text.starts_with("AGE-SECRET-KEY-1")
+        || text.starts_with("untrusted comment: minisign")
+}
+
+fn has_percent_escapes(text: &str) -> bool {
+    text.as_bytes()
+        .windows(3)
+        .any(|w| w[0] == b'%' && w[1].is_ascii_hexdigit() && w[2].is_ascii_hexdigit())
+}
+
+fn layer(encoding: &str, detail: Option<String>) -> Layer {
+    Layer {
+        encoding: encoding.to_string(),
+        detail,
+    }
+}
+
+// printable utf-8, empty data isn't worth calling text
+fn text(data: &[u8]) -> Option<&str> {
+    text_of(data).filter(|text| !text.trim().is_empty())
+}
+
+fn text_of(data: &[u8]) -> Option<&str> {
+    let text = std::str::from_utf8(data).ok()?;
+    let printable = text
+        .chars()
+        .all(|c| !c.is_control() || matches!(c, '\n' | '\r' | '\t'));
+    printable.then_some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+    use base64::Engine as _;
+    use flate2::write::GzEncoder;
+
+    use super::*;
+    use crate::process_jwt_sign;
+
+    fn encodings(inspection: &Inspection) -> Vec<&str> {
+        inspection
+            .layers
+            .iter()
+            .map(|l| l.encoding.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_inspect_nested_layers() -> anyhow::Result<()> {
+        let mut gz = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gz.write_all(br#"{"user":"alice"}"#)?;
+        let hex = hex::encode(gz.finish()?);
+        let input = STANDARD.encode(hex);
+
+        let inspection = process_inspect(&input, 8)?;
+        assert_eq!(encodings(&inspection), ["base64", "hex", "gzip"]);
+        assert_eq!(inspection.payload, br#"{"user":"alice"}"#);
+        assert!(inspection.to_string().contains("payload (json)"));
+
+        let inspection = process_inspect(&input, 1)?;
+        assert_eq!(encodings(&inspection), ["base64"]);
+
+        let mut gz = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gz.write_all(&vec![b'a'; MAX_INSPECT_LEN + 1])?;
+        let inspection = process_inspect(&STANDARD.encode(gz.finish()?), 8)?;
+        assert_eq!(encodings(&inspection), ["base64", "gzip"]);
+        assert!(inspection.layers[1]
+            .detail
+            .as_deref()
+            .unwrap()
+            .starts_with("truncated"));
+        assert_eq!(inspection.payload.len(), MAX_INSPECT_LEN);
+
+        let inspection = process_inspect("a%3Db%26c%3D%F0%9F%A6%80", 8)?;
+        assert_eq!(encodings(&inspection), ["url-encoded"]);
+        assert_eq!(inspection.payload, "a=b&c=🦀".as_bytes());
+        Ok(())
+    }
+
+    #[test]
+    fn test_inspect_jwt_and_keys() -> anyhow::Result<()> {
+        let token = process_jwt_sign("alice", "device1", 1, "secret")?;
+        let inspection = process_inspect(&token, 8)?;
+        assert_eq!(encodings(&inspection), ["jwt (signature not verified)"]);
+        let detail = inspection.layers[0].detail.as_deref().unwrap();
+        assert!(detail.starts_with("alg HS256, expired"));
+        let claims: serde_json::Value = serde_json::from_slice(&inspection.payload)?;
+        assert_eq!(claims["sub"], "alice");
+
+        let inspection = process_inspect("fixtures/rsa-pss.pk", 8)?;
+        assert_eq!(encodings(&inspection), ["key"]);
+        assert_eq!(
+            inspection.layers[0].detail.as_deref(),
+            Some("rsa-pss public key (2048 bits)")
+        );
+
+        let nested = STANDARD.encode(URL_SAFE_NO_PAD.encode("not a key"));
+        let inspection = process_inspect(&nested, 8)?;
+        assert_eq!(encodings(&inspection), ["base64", "base64"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_inspect_plain_text() -> anyhow::Result<()> {
+        for input in [
+            "hello",
+            "test",
+            "just some words",
+            "a longer sentence of plain words",
+        ] {
+            let inspection = process_inspect(input, 8)?;
+            assert!(inspection.layers.is_empty(), "{}", input);
+            assert_eq!(inspection.payload, input.as_bytes());
+        }
+        let inspection = process_inspect("deadbeef", 8)?;
+        assert_eq!(encodings(&inspection), ["hex"]);
+        assert!(inspection.to_string().ends_with("deadbeef"));
+        Ok(())
+    }
+}
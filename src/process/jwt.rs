@@ -1,6 +1,9 @@
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use jsonwebtoken::errors::ErrorKind;
+use jsonwebtoken::{decode, decode_header, encode, DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
 
+use crate::process::verify::VerifyResult;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
     sub: String,
@@ -24,7 +27,11 @@ pub fn process_jwt_sign(sub: &str, aud: &str, exp: usize, secret: &str) -> anyho
     Ok(token)
 }
 
-pub fn process_jwt_verify(token: &str, secret: &str) -> bool {
+/// Verify a HS256 token. A bad signature or expired token is an invalid result,
+/// a token that can't be parsed at all is an error
+pub fn process_jwt_verify(token: &str, secret: &str) -> anyhow::Result<VerifyResult> {
+    let header = decode_header(token)?;
+    let algorithm = format!("{:?}", header.alg);
     let mut validation = Validation::default();
     validation.validate_aud = false;
     let token_data = decode::<Claims>(
@@ -32,14 +39,63 @@ pub fn process_jwt_verify(token: &str, secret: &str) -> bool {
         &DecodingKey::from_secret(secret.as_ref()),
         &validation,
     );
-    match token_data {
-        Ok(token) => {
-            println!("{:?}", token.claims);
-            true
-        }
-        Err(e) => {
-            println!("{:?}", e);
-            false
-        }
+    let result = match token_data {
+        Ok(token) => VerifyResult {
+            claims: Some(serde_json::to_value(token.claims)?),
+            ..VerifyResult::new(true, algorithm)
+        },
+        Err(e) => match e.kind() {
+            ErrorKind::InvalidSignature => VerifyResult::new(false, algorithm),
+            ErrorKind::ExpiredSignature
+            | ErrorKind::ImmatureSignature
+            | ErrorKind::InvalidAlgorithm
+            | ErrorKind::InvalidIssuer
+            | ErrorKind::InvalidSubject
+            | ErrorKind::MissingRequiredClaim(_) => VerifyResult::invalid(algorithm, e.to_string()),
+            _ => return Err(e.into()),
+        },
+    };
+    Ok(result.with_key_id(header.kid))
+}
+
+/// Decode the header and claims without checking the signature or any claim,
+/// only for showing what a token carries
+pub fn decode_jwt_unverified(token: &str) -> anyhow::Result<(Header, serde_json::Value)> {
+    let mut validation = Validation::default();
+    validation.insecure_disable_signature_validation();
+    validation.validate_exp = false;
+    validation.validate_aud = false;
+    validation.required_spec_claims.clear();
+    let token = decode::<serde_json::Value>(token, &DecodingKey::from_secret(&[]), &validation)?;
+    Ok((token.header, token.claims))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jwt_verify_result() -> anyhow::Result<()> {
+        let exp = chrono::Utc::now().timestamp() as usize + 60;
+        let token = process_jwt_sign("alice", "device1", exp, "secret")?;
+        let result = process_jwt_verify(&token, "secret")?;
+        assert!(result.valid);
+        assert_eq!(result.algorithm, "HS256");
+        assert_eq!(result.claims.unwrap()["sub"], "alice");
+
+        let result = process_jwt_verify(&token, "wrong")?;
+        assert!(!result.valid);
+        assert_eq!(result.reason.as_deref(), Some("signature mismatch"));
+
+        let expired = process_jwt_sign("alice", "device1", exp - 3600, "secret")?;
+        let result = process_jwt_verify(&expired, "secret")?;
+        assert!(!result.valid);
+        assert_eq!(result.reason.as_deref(), Some("ExpiredSignature"));
+        let (header, claims) = decode_jwt_unverified(&expired)?;
+        assert_eq!(format!("{:?}", header.alg), "HS256");
+        assert_eq!(claims["aud"], "device1");
+
+        assert!(process_jwt_verify("not.a.token", "secret").is_err());
+        Ok(())
     }
 }
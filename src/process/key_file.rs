@@ -0,0 +1,162 @@
+use std::path::Path;
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand::rngs::OsRng;
+use rand::RngCore;
+
+use crate::process::vault::resolve_vault_ref;
+use crate::utils::get_passphrase;
+
+const ENCRYPTED_KEY_LABEL: &str = "RCLI ENCRYPTED KEY";
+const ENCRYPTED_KEY_VERSION: u8 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+// version(1) m_cost(4) t_cost(4) p_cost(4) salt(16) nonce(12)
+const HEADER_LEN: usize = 1 + 12 + SALT_LEN + NONCE_LEN;
+// the kdf parameters come from the file before anything is authenticated, a crafted
+// file could otherwise ask for gigabytes of memory or hours of work
+const MAX_M_COST: u32 = 256 * 1024;
+const MAX_T_COST: u32 = 16;
+const MAX_P_COST: u32 = 8;
+
+/// Read a key file (or a `vault:name` secret), decrypting it first if it is passphrase protected.
+/// The passphrase comes from `RCLI_KEY_PASSPHRASE` or an interactive prompt.
+pub fn read_key_file(path: impl AsRef<Path>) -> anyhow::Result<Vec<u8>> {
+    read_key_file_with(path, || get_passphrase(false))
+}
+
+/// Like [`read_key_file`], asking `passphrase` for the passphrase only if the key is encrypted
+pub fn read_key_file_with(
+    path: impl AsRef<Path>,
+    passphrase: impl FnOnce() -> anyhow::Result<String>,
+) -> anyhow::Result<Vec<u8>> {
+    let path = path.as_ref();
+    let content = match path.to_str().map(resolve_vault_ref).transpose()?.flatten() {
+        Some(content) => content,
+        None => std::fs::read(path)?,
+    };
+    if !is_encrypted_key(&content) {
+        return Ok(content);
+    }
+    decrypt_key(&content, &passphrase()?)
+}
+
+pub fn is_encrypted_key(content: &[u8]) -> bool {
+    let label = format!("-----BEGIN {}-----", ENCRYPTED_KEY_LABEL);
+    content.trim_ascii_start().starts_with(label.as_bytes())
+}
+
+/// Encrypt a key with ChaCha20-Poly1305 under an Argon2id derived key and wrap it in PEM armor
+pub fn encrypt_key(key: &[u8], passphrase: &str) -> anyhow::Result<Vec<u8>> {
+    seal(ENCRYPTED_KEY_LABEL, key, passphrase)
+}
+
+pub fn decrypt_key(content: &[u8], passphrase: &str) -> anyhow::Result<Vec<u8>> {
+    open(ENCRYPTED_KEY_LABEL, content, passphrase)
+}
+
+/// Encrypt `data` with a passphrase into a PEM block with the given label
+pub fn seal(label: &str, data: &[u8], passphrase: &str) -> anyhow::Result<Vec<u8>> {
+    let params = Params::default();
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    header.push(ENCRYPTED_KEY_VERSION);
+    header.extend_from_slice(&params.m_cost().to_le_bytes());
+    header.extend_from_slice(&params.t_cost().to_le_bytes());
+    header.extend_from_slice(&params.p_cost().to_le_bytes());
+    header.extend_from_slice(&salt);
+    header.extend_from_slice(&nonce);
+
+    let cipher = ChaCha20Poly1305::new(&derive_key(passphrase, &salt, params)?);
+    // the header is authenticated so the kdf parameters can't be tampered with
+    let payload = Payload {
+        msg: data,
+        aad: &header,
+    };
+    let ciphertext = cipher.encrypt(Nonce::from_slice(&nonce), payload)?;
+
+    header.extend_from_slice(&ciphertext);
+    let pem = pem_rfc7468::encode_string(label, pem_rfc7468::LineEnding::LF, &header)?;
+    Ok(pem.into_bytes())
+}
+
+/// Decrypt a PEM block created by [`seal`] with the same label
+pub fn open(label: &str, content: &[u8], passphrase: &str) -> anyhow::Result<Vec<u8>> {
+    let (actual, data) = pem_rfc7468::decode_vec(content.trim_ascii())?;
+    if actual != label || data.len() < HEADER_LEN || data[0] != ENCRYPTED_KEY_VERSION {
+        anyhow::bail!("invalid {} file", label.to_lowercase());
+    }
+    let (header, ciphertext) = data.split_at(HEADER_LEN);
+    let u32_at =
+        |i: usize| u32::from_le_bytes([header[i], header[i + 1], header[i + 2], header[i + 3]]);
+    let (m_cost, t_cost, p_cost) = (u32_at(1), u32_at(5), u32_at(9));
+    if m_cost > MAX_M_COST || t_cost > MAX_T_COST || p_cost > MAX_P_COST {
+        anyhow::bail!(
+            "{} asks for argon2 m={} t={} p={}, above the limit of m={} t={} p={}",
+            label.to_lowercase(),
+            m_cost,
+            t_cost,
+            p_cost,
+            MAX_M_COST,
+            MAX_T_COST,
+            MAX_P_COST
+        );
+    }
+    let params = Params::new(m_cost, t_cost, p_cost, None)?;
+    let salt = &header[13..13 + SALT_LEN];
+    let nonce = &header[13 + SALT_LEN..];
+
+    let cipher = ChaCha20Poly1305::new(&derive_key(passphrase, salt, params)?);
+    let payload = Payload {
+        msg: ciphertext,
+        aad: header,
+    };
+    cipher
+        .decrypt(Nonce::from_slice(nonce), payload)
+        .map_err(|_| anyhow::anyhow!("failed to decrypt: wrong passphrase or corrupted file"))
+}
+
+fn derive_key(passphrase: &str, salt: &[u8], params: Params) -> anyhow::Result<Key> {
+    let mut key = Key::default();
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params).hash_password_into(
+        passphrase.as_bytes(),
+        salt,
+        &mut key,
+    )?;
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_decrypt_key() -> anyhow::Result<()> {
+        let key = std::fs::read("fixtures/ed25519.sk")?;
+        let encrypted = encrypt_key(&key, "correct horse")?;
+        assert!(is_encrypted_key(&encrypted));
+        assert!(!is_encrypted_key(&key));
+        assert_eq!(decrypt_key(&encrypted, "correct horse")?, key);
+        assert!(decrypt_key(&encrypted, "wrong horse").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_open_rejects_expensive_params() -> anyhow::Result<()> {
+        let encrypted = encrypt_key(b"secret", "correct horse")?;
+        let (_, mut data) = pem_rfc7468::decode_vec(&encrypted)?;
+        // 4 TiB of memory
+        data[1..5].copy_from_slice(&u32::MAX.to_le_bytes());
+        let crafted =
+            pem_rfc7468::encode_string(ENCRYPTED_KEY_LABEL, pem_rfc7468::LineEnding::LF, &data)?;
+        let err = decrypt_key(crafted.as_bytes(), "correct horse").unwrap_err();
+        assert!(err.to_string().contains("above the limit"));
+        Ok(())
+    }
+}
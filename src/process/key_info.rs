@@ -0,0 +1,239 @@
+use std::fmt;
+use std::path::Path;
+
+use base64::engine::general_purpose::STANDARD_NO_PAD;
+use base64::Engine as _;
+
+use crate::process::key_file::read_key_file;
+use crate::process::text::describe_key;
+use crate::TextKeyType;
+
+const RANDOMART_WIDTH: usize = 17;
+const RANDOMART_HEIGHT: usize = 9;
+const RANDOMART_SYMBOLS: &[u8] = b" .o+=*BOX@%&#/^";
+
+#[derive(Debug)]
+pub struct KeyInfo {
+    pub algorithm: TextKeyType,
+    pub private: bool,
+    pub bits: usize,
+    pub key_id: Option<String>,
+    pub public_key: Option<String>,
+    /// `BLAKE3:` followed by the unpadded base64 hash, like OpenSSH's `SHA256:` fingerprints
+    pub fingerprint: String,
+    pub randomart: String,
+    /// other key types the content is equally valid for, `--format` picks one
+    pub ambiguous_with: Vec<TextKeyType>,
+}
+
+impl fmt::Display for KeyInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match (self.private, &self.public_key) {
+            (true, Some(_)) => "private",
+            (true, None) => "shared secret",
+            (false, _) => "public",
+        };
+        writeln!(f, "algorithm: {}", self.algorithm)?;
+        if !self.ambiguous_with.is_empty() {
+            let others = self
+                .ambiguous_with
+                .iter()
+                .map(|key_type| key_type.to_string())
+                .collect::<Vec<_>>();
+            writeln!(
+                f,
+                "note: the key is also a valid {} key, pass --format if it is one",
+                others.join(" or ")
+            )?;
+        }
+        writeln!(f, "type: {} ({} bits)", kind, self.bits)?;
+        if let Some(key_id) = &self.key_id {
+            writeln!(f, "key id: {}", key_id)?;
+        }
+        if let Some(public_key) = &self.public_key {
+            writeln!(f, "public key: {}", public_key)?;
+        }
+        writeln!(f, "fingerprint: {}", self.fingerprint)?;
+        write!(f, "{}", self.randomart)
+    }
+}
+
+/// Describe a key file. The algorithm comes from `key_type`, the `<type>.sk`/`<type>.pk`
+/// names `text generate` writes, or is detected from the content.
+pub fn process_key_info(
+    path: impl AsRef<Path>,
+    key_type: Option<TextKeyType>,
+) -> anyhow::Result<KeyInfo> {
+    let path = path.as_ref();
+    let content = read_key_file(path)?;
+    let public = matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("pk" | "pub")
+    );
+    let named_type = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .and_then(|s| s.parse().ok());
+    let key = match (key_type, named_type) {
+        (Some(key_type), _) => describe_key(&content, Some(key_type), public)?,
+        (None, Some(named)) => describe_key(&content, Some(named), public)
+            .or_else(|_| describe_key(&content, None, public))?,
+        (None, None) => describe_key(&content, None, public)?,
+    };
+    let hash = blake3::hash(&key.fingerprint_input);
+    let title = format!("{} {}", key.key_type, key.bits).to_uppercase();
+    Ok(KeyInfo {
+        algorithm: key.key_type,
+        private: key.private,
+        bits: key.bits,
+        key_id: key.key_id,
+        public_key: key.public_key,
+        fingerprint: format!("BLAKE3:{}", STANDARD_NO_PAD.encode(hash.as_bytes())),
+        randomart: randomart(hash.as_bytes(), &title, "BLAKE3"),
+        ambiguous_with: key.ambiguous_with,
+    })
+}
+
+/// OpenSSH's "drunken bishop" visualisation of a fingerprint
+fn randomart(hash: &[u8], title: &str, footer: &str) -> String {
+    let mut field = [[0usize; RANDOMART_WIDTH]; RANDOMART_HEIGHT];
+    let (mut x, mut y) = (RANDOMART_WIDTH / 2, RANDOMART_HEIGHT / 2);
+    let (start_x, start_y) = (x, y);
+    for byte in hash {
+        let mut bits = *byte;
+        for _ in 0..4 {
+            x = match bits & 0x1 {
+                0 => x.saturating_sub(1),
+                _ => (x + 1).min(RANDOMART_WIDTH - 1),
+            };
+            y = match bits & 0x2 {
+                0 => y.saturating_sub(1),
+                _ => (y + 1).min(RANDOMART_HEIGHT - 1),
+            };
+            field[y][x] += 1;
+            bits >>= 2;
+        }
+    }
+
+    let mut art = border(title);
+    for (row, cells) in field.iter().enumerate() {
+        art.push('|');
+        for (col, count) in cells.iter().enumerate() {
+            let symbol = if (col, row) == (start_x, start_y) {
+                'S'
+            } else if (col, row) == (x, y) {
+                'E'
+            } else {
+                RANDOMART_SYMBOLS[(*count).min(RANDOMART_SYMBOLS.len() - 1)] as char
+            };
+            art.push(symbol);
+        }
+        art.push_str("|\n");
+    }
+    art.push_str(&border(footer));
+    art
+}
+
+fn border(label: &str) -> String {
+    let label = format!("[{}]", label);
+    let left = RANDOMART_WIDTH.saturating_sub(label.len()) / 2;
+    let right = RANDOMART_WIDTH.saturating_sub(label.len() + left);
+    format!("+{}{}{}+\n", "-".repeat(left), label, "-".repeat(right))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{process_generate_key, KeyFormat};
+
+    #[test]
+    fn test_key_info_derives_public_key() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        for key_format in [KeyFormat::Raw, KeyFormat::Pem, KeyFormat::OpenSsh] {
+            let keys = process_generate_key(TextKeyType::Ed25519, key_format, None)?;
+            let sk = dir.path().join(format!("{}.sk", key_format));
+            let pk = dir.path().join(format!("{}.pk", key_format));
+            std::fs::write(&sk, &keys[0])?;
+            std::fs::write(&pk, &keys[1])?;
+
+            let private = process_key_info(&sk, None)?;
+            let public = process_key_info(&pk, None)?;
+            assert!(matches!(private.algorithm, TextKeyType::Ed25519));
+            assert!(private.private && !public.private);
+            assert_eq!(private.fingerprint, public.fingerprint);
+            assert_eq!(private.public_key, public.public_key);
+            assert!(private.public_key.unwrap().starts_with("ssh-ed25519 "));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_key_info_detects_formats() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let cases = [
+            (TextKeyType::P256, KeyFormat::Pem),
+            (TextKeyType::Secp256k1, KeyFormat::Pem),
+            (TextKeyType::Minisign, KeyFormat::Raw),
+            (TextKeyType::X25519, KeyFormat::Age),
+            (TextKeyType::Blake3, KeyFormat::Raw),
+        ];
+        for (format, key_format) in cases {
+            let keys = process_generate_key(format, key_format, None)?;
+            let path = dir.path().join("key");
+            std::fs::write(&path, &keys[0])?;
+            let info = process_key_info(&path, None)?;
+            assert_eq!(info.algorithm.to_string(), format.to_string());
+            assert!(info.private);
+        }
+        let info = process_key_info("fixtures/rsa-pss.pk", None)?;
+        assert!(matches!(info.algorithm, TextKeyType::RsaPss));
+        assert_eq!(info.bits, 2048);
+        Ok(())
+    }
+
+    #[test]
+    fn test_key_info_hex_ed25519_key() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let seed = [42u8; 32];
+        let public_key = ed25519_dalek::SigningKey::from_bytes(&seed).verifying_key();
+        let path = dir.path().join("derived.key");
+        std::fs::write(&path, hex::encode(seed))?;
+
+        let detected = process_key_info(&path, None)?;
+        assert!(matches!(detected.algorithm, TextKeyType::Blake3));
+        assert!(matches!(
+            detected.ambiguous_with[..],
+            [TextKeyType::Ed25519]
+        ));
+        assert!(detected.to_string().contains("also a valid ed25519 key"));
+
+        let info = process_key_info(&path, Some(TextKeyType::Ed25519))?;
+        assert!(info.ambiguous_with.is_empty());
+        assert!(info.private);
+        let openssh = ssh_key::PublicKey::from_openssh(info.public_key.as_deref().unwrap())?;
+        let openssh = openssh.key_data().ed25519().unwrap().0;
+        assert_eq!(openssh, public_key.to_bytes());
+
+        // `text generate` names the file after the key type, which settles it
+        let named = dir.path().join("ed25519.sk");
+        std::fs::write(&named, hex::encode(seed))?;
+        assert!(matches!(
+            process_key_info(&named, None)?.algorithm,
+            TextKeyType::Ed25519
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_randomart_shape() {
+        let art = randomart(&[0u8; 32], "ED25519 256", "BLAKE3");
+        let lines = art.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), RANDOMART_HEIGHT + 2);
+        assert!(lines.iter().all(|l| l.len() == RANDOMART_WIDTH + 2));
+        assert_eq!(lines[0], "+--[ED25519 256]--+");
+        assert_eq!(lines[10], "+----[BLAKE3]-----+");
+        // all zero bits walk up-left into the corner
+        assert_eq!(&lines[1][..2], "|E");
+        assert!(art.contains('S'));
+    }
+}
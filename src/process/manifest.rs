@@ -0,0 +1,228 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine as _;
+use walkdir::WalkDir;
+
+use crate::process::text::{Ed25519Signer, Ed25519Verifier, KeyLoader, TextSign, TextVerify};
+use crate::utils::{escape_path, unescape_path};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestEntry {
+    pub path: String,
+    pub size: u64,
+    pub hash: String,
+}
+
+#[derive(Debug, Default)]
+pub struct ManifestReport {
+    pub tampered: Vec<String>,
+    pub missing: Vec<String>,
+    pub extra: Vec<String>,
+}
+
+impl ManifestEntry {
+    fn from_file(path: String, file: &Path) -> anyhow::Result<Self> {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update_reader(File::open(file)?)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            path,
+            size,
+            hash: hasher.finalize().to_hex().to_string(),
+        })
+    }
+}
+
+impl ManifestReport {
+    pub fn is_valid(&self) -> bool {
+        self.tampered.is_empty() && self.missing.is_empty() && self.extra.is_empty()
+    }
+}
+
+/// Hash every file under `dir` and return the manifest with its base64 encoded ed25519 signature.
+/// Files listed in `exclude` (e.g. the manifest itself) are skipped.
+pub fn process_manifest_sign(
+    dir: &Path,
+    key: &str,
+    exclude: &[&str],
+) -> anyhow::Result<(String, String)> {
+    let signer = Ed25519Signer::load(key)?;
+    let entries = scan_dir(dir, exclude)?;
+    let manifest = entries
+        .iter()
+        .map(|(path, file)| {
+            ManifestEntry::from_file(path.clone(), file).map(|e| format!("{}\n", e))
+        })
+        .collect::<anyhow::Result<String>>()?;
+    let signature = signer.sign(&mut manifest.as_bytes())?;
+    Ok((manifest, URL_SAFE_NO_PAD.encode(signature)))
+}
+
+/// Verify the manifest signature, then compare the manifest against the files under `dir`
+pub fn process_manifest_verify(
+    dir: &Path,
+    manifest: &str,
+    key: &str,
+    sig: &str,
+    exclude: &[&str],
+) -> anyhow::Result<ManifestReport> {
+    let verifier = Ed25519Verifier::load(key)?;
+    let content = std::fs::read_to_string(manifest)?;
+    let sig = URL_SAFE_NO_PAD.decode(sig.trim())?;
+    if !verifier.verify(content.as_bytes(), &sig)? {
+        anyhow::bail!("manifest signature is invalid");
+    }
+
+    let mut files = scan_dir(dir, exclude)?;
+    let mut report = ManifestReport::default();
+    for line in content.lines().filter(|l| !l.is_empty()) {
+        let expected: ManifestEntry = line.parse()?;
+        match files.remove(&expected.path) {
+            Some(file) => {
+                let actual = ManifestEntry::from_file(expected.path.clone(), &file)?;
+                if actual != expected {
+                    report.tampered.push(expected.path);
+                }
+            }
+            None => report.missing.push(expected.path),
+        }
+    }
+    report.extra = files.into_keys().collect();
+    Ok(report)
+}
+
+/// Collect all regular files under `dir` keyed by their `/` separated relative path
+fn scan_dir(dir: &Path, exclude: &[&str]) -> anyhow::Result<BTreeMap<String, PathBuf>> {
+    let exclude = exclude
+        .iter()
+        .filter_map(|p| Path::new(p).canonicalize().ok())
+        .collect::<Vec<_>>();
+    let mut files = BTreeMap::new();
+    for entry in WalkDir::new(dir) {
+        let entry = entry?;
+        if !entry.file_type().is_file() || exclude.contains(&entry.path().canonicalize()?) {
+            continue;
+        }
+        let relative = entry.path().strip_prefix(dir)?;
+        let relative = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        files.insert(relative, entry.into_path());
+    }
+    Ok(files)
+}
+
+impl std::fmt::Display for ManifestEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match escape_path(&self.path) {
+            Some(path) => write!(f, "\\{}  {}  {}", self.hash, self.size, path),
+            None => write!(f, "{}  {}  {}", self.hash, self.size, self.path),
+        }
+    }
+}
+
+impl std::str::FromStr for ManifestEntry {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (escaped, line) = match s.strip_prefix('\\') {
+            Some(line) => (true, line),
+            None => (false, s),
+        };
+        let mut parts = line.splitn(3, "  ");
+        let (Some(hash), Some(size), Some(path)) = (parts.next(), parts.next(), parts.next())
+        else {
+            anyhow::bail!("invalid manifest line: {}", s);
+        };
+        let path = match escaped {
+            true => unescape_path(path)?,
+            false => path.to_string(),
+        };
+        Ok(Self {
+            path,
+            size: size.parse()?,
+            hash: hash.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest_sign_verify() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::create_dir(dir.path().join("sub"))?;
+        std::fs::write(dir.path().join("a.txt"), "hello")?;
+        std::fs::write(dir.path().join("sub/b.txt"), "world")?;
+        std::fs::write(dir.path().join("sub/c.txt"), "!")?;
+
+        let (manifest, sig) = process_manifest_sign(dir.path(), "fixtures/ed25519.sk", &[])?;
+        assert_eq!(manifest.lines().count(), 3);
+        let manifest_path = dir.path().join("manifest.txt");
+        std::fs::write(&manifest_path, &manifest)?;
+        let manifest_path = manifest_path.to_str().unwrap();
+
+        let verify = || {
+            process_manifest_verify(
+                dir.path(),
+                manifest_path,
+                "fixtures/ed25519.pk",
+                &sig,
+                &[manifest_path],
+            )
+        };
+        assert!(verify()?.is_valid());
+
+        std::fs::write(dir.path().join("a.txt"), "hellO")?;
+        std::fs::remove_file(dir.path().join("sub/b.txt"))?;
+        std::fs::write(dir.path().join("d.txt"), "new")?;
+        let report = verify()?;
+        assert_eq!(report.tampered, vec!["a.txt"]);
+        assert_eq!(report.missing, vec!["sub/b.txt"]);
+        assert_eq!(report.extra, vec!["d.txt"]);
+
+        std::fs::write(manifest_path, manifest.replace("  5  a.txt", "  6  a.txt"))?;
+        assert!(verify().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_manifest_escapes_paths() -> anyhow::Result<()> {
+        for path in ["two\nlines", "back\\slash", " padded \r", "  plain  "] {
+            let entry = ManifestEntry {
+                path: path.to_string(),
+                size: 5,
+                hash: "ab".repeat(32),
+            };
+            let line = entry.to_string();
+            assert_eq!(line.lines().count(), 1);
+            assert_eq!(line.parse::<ManifestEntry>()?, entry);
+        }
+        assert!("\\ab  1  bad\\x".parse::<ManifestEntry>().is_err());
+
+        let dir = tempfile::tempdir()?;
+        std::fs::write(dir.path().join("new\nline.txt"), "hello")?;
+        std::fs::write(dir.path().join("trailing "), "world")?;
+        let (manifest, sig) = process_manifest_sign(dir.path(), "fixtures/ed25519.sk", &[])?;
+        assert_eq!(manifest.lines().count(), 2);
+        let manifest_path = dir.path().join("manifest.txt");
+        std::fs::write(&manifest_path, &manifest)?;
+        let manifest_path = manifest_path.to_str().unwrap();
+        let report = process_manifest_verify(
+            dir.path(),
+            manifest_path,
+            "fixtures/ed25519.pk",
+            &sig,
+            &[manifest_path],
+        )?;
+        assert!(report.is_valid());
+        Ok(())
+    }
+}
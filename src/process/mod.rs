@@ -1,6 +1,20 @@
 pub mod b64;
+pub mod codec;
 pub mod csv_convert;
+pub mod derive;
 pub mod gen_pass;
+pub mod hash;
+pub mod hexdump;
 pub mod http_serve;
+pub mod id;
+pub mod inspect;
 pub mod jwt;
+pub mod key_file;
+pub mod key_info;
+pub mod manifest;
+pub mod qr;
+pub mod shamir;
 pub mod text;
+pub mod url;
+pub mod vault;
+pub mod verify;
@@ -0,0 +1,150 @@
+use qrcode::render::{svg, unicode::Dense1x2};
+use qrcode::{Color, EcLevel, QrCode};
+
+use crate::utils::read_line_input;
+use crate::{QrEcLevel, QrFormat};
+
+// the spec asks for a 4 module light border
+const QUIET_ZONE: usize = 4;
+// a version 40 code at 64 pixels per module is already an 11840 pixel square
+const MAX_SCALE: u32 = 64;
+
+impl From<QrEcLevel> for EcLevel {
+    fn from(level: QrEcLevel) -> Self {
+        match level {
+            QrEcLevel::Low => EcLevel::L,
+            QrEcLevel::Medium => EcLevel::M,
+            QrEcLevel::Quartile => EcLevel::Q,
+            QrEcLevel::High => EcLevel::H,
+        }
+    }
+}
+
+/// Render the input as a QR code. The terminal rendering draws two rows per line with
+/// half blocks, light modules are the foreground so it scans on a dark terminal unless
+/// `invert` is set. `scale` is the pixel size of a module for PNG and SVG.
+pub fn process_qr_encode(
+    input: &str,
+    ec_level: QrEcLevel,
+    format: QrFormat,
+    scale: u32,
+    invert: bool,
+) -> anyhow::Result<Vec<u8>> {
+    if !(1..=MAX_SCALE).contains(&scale) {
+        anyhow::bail!("scale must be between 1 and {}", MAX_SCALE);
+    }
+    let data = read_line_input(input)?;
+    if data.is_empty() {
+        anyhow::bail!("nothing to encode");
+    }
+    let code = QrCode::with_error_correction_level(&data, ec_level.into())
+        .map_err(|e| anyhow::anyhow!("can't encode {} bytes as a QR code: {}", data.len(), e))?;
+    let output = match format {
+        QrFormat::Terminal => {
+            let (dark, light) = match invert {
+                true => (Dense1x2::Dark, Dense1x2::Light),
+                false => (Dense1x2::Light, Dense1x2::Dark),
+            };
+            let mut text = code
+                .render::<Dense1x2>()
+                .dark_color(dark)
+                .light_color(light)
+                .build();
+            text.push('\n');
+            text.into_bytes()
+        }
+        QrFormat::Svg => code
+            .render::<svg::Color>()
+            .module_dimensions(scale, scale)
+            .build()
+            .into_bytes(),
+        QrFormat::Png => render_png(&code, scale as usize)?,
+    };
+    Ok(output)
+}
+
+fn render_png(code: &QrCode, scale: usize) -> anyhow::Result<Vec<u8>> {
+    let width = code.width();
+    let size = (width + 2 * QUIET_ZONE)
+        .checked_mul(scale)
+        .filter(|size| u32::try_from(*size).is_ok())
+        .ok_or_else(|| anyhow::anyhow!("QR code image is too large"))?;
+    let pixel_count = size
+        .checked_mul(size)
+        .ok_or_else(|| anyhow::anyhow!("QR code image is too large"))?;
+    let mut pixels = vec![0xffu8; pixel_count];
+    for (i, color) in code.to_colors().into_iter().enumerate() {
+        if color == Color::Light {
+            continue;
+        }
+        let x = (i % width + QUIET_ZONE) * scale;
+        let y = (i / width + QUIET_ZONE) * scale;
+        for row in pixels.chunks_mut(size).skip(y).take(scale) {
+            row[x..x + scale].fill(0);
+        }
+    }
+
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, size as u32, size as u32);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&pixels)?;
+    writer.finish()?;
+    Ok(png)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(data: &[u8], format: QrFormat) -> anyhow::Result<Vec<u8>> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("input");
+        std::fs::write(&path, data)?;
+        process_qr_encode(path.to_str().unwrap(), QrEcLevel::Medium, format, 4, false)
+    }
+
+    #[test]
+    fn test_qr_png() -> anyhow::Result<()> {
+        let png = encode(b"https://example.com\n", QrFormat::Png)?;
+        let decoder = png::Decoder::new(png.as_slice());
+        let mut reader = decoder.read_info()?;
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels)?;
+        // version 2 is 25 modules, plus the quiet zone on both sides
+        assert_eq!(info.width, (25 + 2 * 4) * 4);
+        let at = |module_x: usize, module_y: usize| {
+            pixels[(module_y * 4 + 16) * info.width as usize + module_x * 4 + 16]
+        };
+        // the finder pattern in the top left: dark ring, light ring, dark center
+        assert_eq!((at(0, 0), at(1, 1), at(3, 3)), (0, 0xff, 0));
+        assert_eq!(pixels[0], 0xff);
+        Ok(())
+    }
+
+    #[test]
+    fn test_qr_terminal_and_svg() -> anyhow::Result<()> {
+        let text = String::from_utf8(encode(b"hello", QrFormat::Terminal)?)?;
+        // version 1 is 21 modules, 29 with the quiet zone, two rows per line
+        assert_eq!(text.lines().count(), 15);
+        assert!(text.lines().all(|line| line.chars().count() == 29));
+
+        let svg = String::from_utf8(encode(b"hello", QrFormat::Svg)?)?;
+        assert!(svg.contains("<svg") && svg.contains(r#"width="116""#));
+
+        assert!(encode(b"\n", QrFormat::Terminal).is_err());
+        assert!(encode(&[b'x'; 4000], QrFormat::Terminal).is_err());
+
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("input");
+        std::fs::write(&path, b"hello")?;
+        let path = path.to_str().unwrap();
+        for scale in [0, 65, 100_000] {
+            assert!(
+                process_qr_encode(path, QrEcLevel::Medium, QrFormat::Png, scale, false).is_err()
+            );
+        }
+        Ok(())
+    }
+}
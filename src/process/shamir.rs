@@ -0,0 +1,284 @@
+use std::collections::BTreeMap;
+use std::io::Read;
+
+use rand::rngs::OsRng;
+use rand::RngCore;
+
+use crate::utils::get_reader;
+
+const SHARE_LABEL: &str = "RCLI SECRET SHARE";
+const SHARE_VERSION: u8 = 1;
+const SET_ID_LEN: usize = 8;
+const CHECKSUM_LEN: usize = 4;
+// version(1) threshold(1) index(1) set id(8)
+const SHARE_HEADER_LEN: usize = 3 + SET_ID_LEN;
+
+/// One share of a secret. Shares from the same split carry the same random set id,
+/// so mixing up shares of different secrets is caught instead of yielding garbage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Share {
+    pub threshold: u8,
+    pub index: u8,
+    pub set_id: [u8; SET_ID_LEN],
+    pub data: Vec<u8>,
+}
+
+impl Share {
+    /// Armor the share as PEM, the payload ends with a truncated blake3 checksum
+    pub fn encode(&self) -> anyhow::Result<String> {
+        let mut payload = Vec::with_capacity(SHARE_HEADER_LEN + self.data.len() + CHECKSUM_LEN);
+        payload.push(SHARE_VERSION);
+        payload.push(self.threshold);
+        payload.push(self.index);
+        payload.extend_from_slice(&self.set_id);
+        payload.extend_from_slice(&self.data);
+        let checksum = blake3::hash(&payload);
+        payload.extend_from_slice(&checksum.as_bytes()[..CHECKSUM_LEN]);
+        let pem = pem_rfc7468::encode_string(SHARE_LABEL, pem_rfc7468::LineEnding::LF, &payload)?;
+        Ok(pem)
+    }
+
+    pub fn decode(content: &str) -> anyhow::Result<Self> {
+        let (label, payload) = pem_rfc7468::decode_vec(content.trim().as_bytes())?;
+        if label != SHARE_LABEL || payload.len() <= SHARE_HEADER_LEN + CHECKSUM_LEN {
+            anyhow::bail!("invalid secret share");
+        }
+        let (payload, checksum) = payload.split_at(payload.len() - CHECKSUM_LEN);
+        if &blake3::hash(payload).as_bytes()[..CHECKSUM_LEN] != checksum {
+            anyhow::bail!("secret share checksum mismatch, the share is corrupted");
+        }
+        if payload[0] != SHARE_VERSION {
+            anyhow::bail!("unsupported secret share version {}", payload[0]);
+        }
+        if payload[1] < 2 {
+            anyhow::bail!("invalid secret share threshold {}", payload[1]);
+        }
+        Ok(Self {
+            threshold: payload[1],
+            index: payload[2],
+            set_id: payload[3..SHARE_HEADER_LEN].try_into()?,
+            data: payload[SHARE_HEADER_LEN..].to_vec(),
+        })
+    }
+}
+
+/// Split `secret` into `shares` shares, any `threshold` of them recover it
+pub fn split_secret(secret: &[u8], shares: u8, threshold: u8) -> anyhow::Result<Vec<Share>> {
+    if threshold < 2 {
+        anyhow::bail!("threshold must be at least 2");
+    }
+    if shares < threshold {
+        anyhow::bail!("shares must be at least the threshold ({})", threshold);
+    }
+    if secret.is_empty() {
+        anyhow::bail!("secret can't be empty");
+    }
+    let mut set_id = [0u8; SET_ID_LEN];
+    OsRng.fill_bytes(&mut set_id);
+    let mut result = (1..=shares)
+        .map(|index| Share {
+            threshold,
+            index,
+            set_id,
+            data: Vec::with_capacity(secret.len()),
+        })
+        .collect::<Vec<_>>();
+    // one random polynomial per secret byte, the constant term is the byte itself
+    let mut coefficients = vec![0u8; threshold as usize];
+    for byte in secret {
+        coefficients[0] = *byte;
+        OsRng.fill_bytes(&mut coefficients[1..]);
+        for share in result.iter_mut() {
+            share.data.push(gf_eval(&coefficients, share.index));
+        }
+    }
+    coefficients.fill(0);
+    Ok(result)
+}
+
+/// Recover the secret from at least `threshold` shares of the same split
+pub fn combine_shares(shares: &[Share]) -> anyhow::Result<Vec<u8>> {
+    let first = shares
+        .first()
+        .ok_or_else(|| anyhow::anyhow!("no secret shares given"))?;
+    let mut unique = BTreeMap::new();
+    for share in shares {
+        if share.set_id != first.set_id || share.threshold != first.threshold {
+            anyhow::bail!("secret shares belong to different splits");
+        }
+        if share.index == 0 || share.threshold < 2 || share.data.len() != first.data.len() {
+            anyhow::bail!("invalid secret share {}", share.index);
+        }
+        // the same share given twice is fine, two different shares with one index are not
+        if let Some(other) = unique.insert(share.index, share) {
+            if other.data != share.data {
+                anyhow::bail!("secret shares {} conflict", share.index);
+            }
+        }
+    }
+    if unique.len() < first.threshold as usize {
+        anyhow::bail!(
+            "need {} distinct shares, got {}",
+            first.threshold,
+            unique.len()
+        );
+    }
+    let shares = unique
+        .into_values()
+        .take(first.threshold as usize)
+        .collect::<Vec<_>>();
+    // lagrange interpolation at x = 0
+    let mut secret = vec![0u8; first.data.len()];
+    for (i, share) in shares.iter().enumerate() {
+        let mut basis = 1u8;
+        for (j, other) in shares.iter().enumerate() {
+            if i != j {
+                // x_j / (x_j - x_i), subtraction is xor in GF(256)
+                basis = gf_mul(basis, gf_div(other.index, other.index ^ share.index));
+            }
+        }
+        for (s, y) in secret.iter_mut().zip(&share.data) {
+            *s ^= gf_mul(*y, basis);
+        }
+    }
+    Ok(secret)
+}
+
+/// Split the secret read from `input` and return the armored shares
+pub fn process_text_split(input: &str, shares: u8, threshold: u8) -> anyhow::Result<Vec<String>> {
+    let mut reader = get_reader(input)?;
+    let mut secret = Vec::new();
+    reader.read_to_end(&mut secret)?;
+    let shares = split_secret(&secret, shares, threshold)?;
+    secret.fill(0);
+    shares.iter().map(Share::encode).collect()
+}
+
+/// Combine armored shares, each input may hold several of them
+pub fn process_text_combine(inputs: &[String]) -> anyhow::Result<Vec<u8>> {
+    let mut shares = Vec::new();
+    for input in inputs {
+        let mut reader = get_reader(input)?;
+        let mut content = String::new();
+        reader.read_to_string(&mut content)?;
+        let begin = format!("-----BEGIN {}-----", SHARE_LABEL);
+        let end = format!("-----END {}-----", SHARE_LABEL);
+        let mut rest = content.as_str();
+        while let Some(start) = rest.find(&begin) {
+            let stop = rest[start..]
+                .find(&end)
+                .ok_or_else(|| anyhow::anyhow!("{}: truncated secret share", input))?;
+            let block = &rest[start..start + stop + end.len()];
+            shares.push(Share::decode(block)?);
+            rest = &rest[start + stop + end.len()..];
+        }
+    }
+    combine_shares(&shares)
+}
+
+// GF(2^8) with the AES polynomial x^8 + x^4 + x^3 + x + 1, branch free so the
+// timing doesn't depend on the secret bytes
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0u8;
+    for _ in 0..8 {
+        product ^= a & 0u8.wrapping_sub(b & 1);
+        let carry = 0u8.wrapping_sub(a >> 7);
+        a = (a << 1) ^ (0x1b & carry);
+        b >>= 1;
+    }
+    product
+}
+
+// a^254 is the inverse of a, b is never zero here
+fn gf_div(a: u8, b: u8) -> u8 {
+    let mut inverse = 1u8;
+    let mut base = b;
+    let mut exp = 254u8;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            inverse = gf_mul(inverse, base);
+        }
+        base = gf_mul(base, base);
+        exp >>= 1;
+    }
+    gf_mul(a, inverse)
+}
+
+// horner's method, coefficients[0] is the constant term
+fn gf_eval(coefficients: &[u8], x: u8) -> u8 {
+    coefficients
+        .iter()
+        .rev()
+        .fold(0u8, |acc, c| gf_mul(acc, x) ^ c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gf_arithmetic() {
+        // worked example from the AES specification
+        assert_eq!(gf_mul(0x57, 0x83), 0xc1);
+        for a in 1..=255u8 {
+            assert_eq!(gf_mul(gf_div(1, a), a), 1);
+        }
+    }
+
+    #[test]
+    fn test_split_combine() -> anyhow::Result<()> {
+        let secret = b"a 32 byte chacha20 master key!!!";
+        let shares = split_secret(secret, 5, 3)?;
+        assert_eq!(shares.len(), 5);
+        assert!(shares.iter().all(|s| s.data != secret));
+
+        assert_eq!(combine_shares(&shares[..3])?, secret);
+        assert_eq!(combine_shares(&shares[2..])?, secret);
+        let picked = [shares[4].clone(), shares[0].clone(), shares[2].clone()];
+        assert_eq!(combine_shares(&picked)?, secret);
+        assert_eq!(combine_shares(&shares)?, secret);
+
+        assert!(combine_shares(&shares[..2]).is_err());
+        let duplicated = [shares[0].clone(), shares[0].clone(), shares[1].clone()];
+        assert!(combine_shares(&duplicated).is_err());
+        let mut tampered = shares[1].clone();
+        tampered.data[0] ^= 1;
+        let conflicting = [
+            shares[0].clone(),
+            shares[1].clone(),
+            tampered,
+            shares[2].clone(),
+        ];
+        assert!(combine_shares(&conflicting).is_err());
+        let other = split_secret(secret, 5, 3)?;
+        assert!(combine_shares(&[shares[0].clone(), shares[1].clone(), other[2].clone()]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_share_armor() -> anyhow::Result<()> {
+        let shares = split_secret(b"secret", 3, 2)?;
+        let encoded = shares[1].encode()?;
+        assert!(encoded.starts_with("-----BEGIN RCLI SECRET SHARE-----"));
+        assert_eq!(Share::decode(&encoded)?, shares[1]);
+
+        let (label, mut payload) = pem_rfc7468::decode_vec(encoded.as_bytes())?;
+        payload[SHARE_HEADER_LEN] ^= 1;
+        let corrupted = pem_rfc7468::encode_string(label, pem_rfc7468::LineEnding::LF, &payload)?;
+        assert!(Share::decode(&corrupted).is_err());
+
+        let single = Share {
+            threshold: 1,
+            ..shares[0].clone()
+        };
+        assert!(Share::decode(&single.encode()?).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_validates_parameters() {
+        assert!(split_secret(b"secret", 5, 1).is_err());
+        assert!(split_secret(b"secret", 2, 3).is_err());
+        assert!(split_secret(b"", 5, 3).is_err());
+    }
+}
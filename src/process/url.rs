@@ -0,0 +1,202 @@
+use std::io::Write;
+
+use percent_encoding::{percent_decode, percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use serde::Serialize;
+use url::Url;
+
+use crate::utils::read_line_input;
+use crate::UrlEncoding;
+
+// everything but RFC 3986 unreserved characters
+const COMPONENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+#[derive(Debug, Serialize)]
+pub struct ParsedUrl {
+    pub scheme: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    /// Port written in the URL, even when it is the scheme's default
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_port: Option<u16>,
+    /// Path as written, `%2F` or invalid utf-8 can't be told apart in the decoded one
+    pub raw_path: String,
+    pub path: String,
+    /// Decoded query parameters in order, keys can repeat
+    pub query: Vec<QueryParam>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fragment: Option<String>,
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct QueryParam {
+    pub key: String,
+    pub value: String,
+}
+
+/// Percent-encode the input, a single trailing newline (from `echo`) is dropped
+pub fn process_url_encode(
+    input: &str,
+    writer: &mut dyn Write,
+    encoding: UrlEncoding,
+) -> anyhow::Result<()> {
+    let data = read_line_input(input)?;
+    match encoding {
+        UrlEncoding::Component => {
+            for part in percent_encode(&data, COMPONENT) {
+                writer.write_all(part.as_bytes())?;
+            }
+        }
+        UrlEncoding::Form => {
+            for part in url::form_urlencoded::byte_serialize(&data) {
+                writer.write_all(part.as_bytes())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Decode percent escapes into raw bytes, form encoding also turns `+` into spaces
+pub fn process_url_decode(
+    input: &str,
+    writer: &mut dyn Write,
+    encoding: UrlEncoding,
+) -> anyhow::Result<()> {
+    let mut data = read_line_input(input)?;
+    if let UrlEncoding::Form = encoding {
+        data.iter_mut()
+            .filter(|b| **b == b'+')
+            .for_each(|b| *b = b' ');
+    }
+    let decoded = percent_decode(&data).collect::<Vec<_>>();
+    writer.write_all(&decoded)?;
+    Ok(())
+}
+
+pub fn process_url_parse(url: &str) -> anyhow::Result<ParsedUrl> {
+    let raw = url.trim();
+    let url = Url::parse(raw)?;
+    let mut without_port = url.clone();
+    let _ = without_port.set_port(None);
+    let username = Some(url.username())
+        .filter(|username| !username.is_empty())
+        .map(|username| {
+            percent_decode(username.as_bytes())
+                .decode_utf8_lossy()
+                .into_owned()
+        });
+    let query = url
+        .query_pairs()
+        .map(|(key, value)| QueryParam {
+            key: key.into_owned(),
+            value: value.into_owned(),
+        })
+        .collect();
+    Ok(ParsedUrl {
+        scheme: url.scheme().to_string(),
+        username,
+        host: url.host_str().map(String::from),
+        port: url.port().or_else(|| explicit_port(raw)),
+        default_port: without_port.port_or_known_default(),
+        raw_path: url.path().to_string(),
+        path: percent_decode(url.path().as_bytes())
+            .decode_utf8_lossy()
+            .into_owned(),
+        query,
+        fragment: url.fragment().map(String::from),
+    })
+}
+
+// the url crate drops a port that equals the scheme's default, find it in the authority
+fn explicit_port(raw: &str) -> Option<u16> {
+    let (_, rest) = raw.split_once("://")?;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    let (host, port) = host.rsplit_once(':')?;
+    // the last ':' of a bracketed IPv6 address without a port
+    if host.starts_with('[') && !host.ends_with(']') {
+        return None;
+    }
+    port.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(
+        f: fn(&str, &mut dyn Write, UrlEncoding) -> anyhow::Result<()>,
+        content: &[u8],
+        encoding: UrlEncoding,
+    ) -> anyhow::Result<String> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("input");
+        std::fs::write(&path, content)?;
+        let mut out = Vec::new();
+        f(path.to_str().unwrap(), &mut out, encoding)?;
+        Ok(String::from_utf8(out)?)
+    }
+
+    #[test]
+    fn test_url_encode_decode() -> anyhow::Result<()> {
+        let input = "a b&c=d/é~\n".as_bytes();
+        let component = run(process_url_encode, input, UrlEncoding::Component)?;
+        assert_eq!(component, "a%20b%26c%3Dd%2F%C3%A9~");
+        let form = run(process_url_encode, input, UrlEncoding::Form)?;
+        assert_eq!(form, "a+b%26c%3Dd%2F%C3%A9%7E");
+
+        let decoded = run(process_url_decode, form.as_bytes(), UrlEncoding::Form)?;
+        assert_eq!(decoded, "a b&c=d/é~");
+        let decoded = run(process_url_decode, b"1+1%3D2", UrlEncoding::Component)?;
+        assert_eq!(decoded, "1+1=2");
+        Ok(())
+    }
+
+    #[test]
+    fn test_url_parse() -> anyhow::Result<()> {
+        let parsed = process_url_parse(
+            "https://alice@api.example.com/v1/my%20files?q=rust+cli&tag=a&tag=b%26c#top",
+        )?;
+        assert_eq!(parsed.scheme, "https");
+        assert_eq!(parsed.username.as_deref(), Some("alice"));
+        assert_eq!(parsed.host.as_deref(), Some("api.example.com"));
+        assert_eq!(parsed.port, None);
+        assert_eq!(parsed.default_port, Some(443));
+        assert_eq!(parsed.raw_path, "/v1/my%20files");
+        assert_eq!(parsed.path, "/v1/my files");
+        assert_eq!(parsed.fragment.as_deref(), Some("top"));
+        let query = parsed
+            .query
+            .iter()
+            .map(|p| (p.key.as_str(), p.value.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(query, [("q", "rust cli"), ("tag", "a"), ("tag", "b&c")]);
+
+        let yaml = crate::OutputFormat::Yaml.serialize(&parsed)?;
+        assert!(yaml.contains("host: api.example.com"));
+        assert!(process_url_parse("not a url").is_err());
+        let parsed = process_url_parse("https://a/x%2Fy/%FF")?;
+        assert_eq!(parsed.raw_path, "/x%2Fy/%FF");
+        assert_eq!(parsed.path, "/x/y/\u{fffd}");
+
+        let ports = |url| -> anyhow::Result<_> {
+            let parsed = process_url_parse(url)?;
+            Ok((parsed.port, parsed.default_port))
+        };
+        assert_eq!(ports("https://a:443/")?, (Some(443), Some(443)));
+        assert_eq!(ports("https://u:p@a:8443")?, (Some(8443), Some(443)));
+        assert_eq!(ports("http://[::1]/x:80")?, (None, Some(80)));
+        assert_eq!(ports("http://[::1]:80")?, (Some(80), Some(80)));
+        assert_eq!(ports("custom://host:9000")?, (Some(9000), None));
+        Ok(())
+    }
+}
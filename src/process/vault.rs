@@ -0,0 +1,176 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine as _;
+use serde::{Deserialize, Serialize};
+
+use crate::process::key_file::{open, seal};
+use crate::utils::{prompt_passphrase, write_secret_file};
+
+const VAULT_LABEL: &str = "RCLI VAULT";
+const VAULT_VERSION: u8 = 1;
+/// Prefix used by other commands to reference a vault secret instead of a file
+pub const VAULT_REF_PREFIX: &str = "vault:";
+
+// set once from the global `--vault` flag before any command runs
+static VAULT_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Vault {
+    version: u8,
+    /// secret name to base64 encoded value, values may be binary keys
+    secrets: BTreeMap<String, String>,
+}
+
+impl Vault {
+    fn load(path: &Path, passphrase: &str) -> anyhow::Result<Self> {
+        let content = std::fs::read(path)
+            .map_err(|e| anyhow::anyhow!("failed to read vault {}: {}", path.display(), e))?;
+        let vault: Vault = serde_json::from_slice(&open(VAULT_LABEL, &content, passphrase)?)?;
+        if vault.version != VAULT_VERSION {
+            anyhow::bail!("unsupported vault version {}", vault.version);
+        }
+        Ok(vault)
+    }
+
+    fn save(&self, path: &Path, passphrase: &str) -> anyhow::Result<()> {
+        let content = seal(VAULT_LABEL, &serde_json::to_vec(self)?, passphrase)?;
+        // write to a temporary file first so a failed write never corrupts the vault
+        let tmp = path.with_extension("tmp");
+        write_secret_file(&tmp, &content)?;
+        std::fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    fn get(&self, name: &str) -> anyhow::Result<Vec<u8>> {
+        let value = self
+            .secrets
+            .get(name)
+            .ok_or_else(|| anyhow::anyhow!("secret {} not found in vault", name))?;
+        Ok(STANDARD.decode(value)?)
+    }
+}
+
+/// Use `path` for the vault commands and `vault:name` references, the first call wins
+pub fn set_vault_path(path: PathBuf) {
+    let _ = VAULT_PATH.set(path);
+}
+
+/// Vault location: the `--vault` flag, `RCLI_VAULT`, then `~/.rcli.vault`, then `rcli.vault`
+/// in the current directory
+pub fn vault_path() -> PathBuf {
+    if let Some(path) = VAULT_PATH.get() {
+        return path.clone();
+    }
+    if let Ok(path) = std::env::var("RCLI_VAULT") {
+        return path.into();
+    }
+    match std::env::var("HOME") {
+        Ok(home) => Path::new(&home).join(".rcli.vault"),
+        Err(_) => "rcli.vault".into(),
+    }
+}
+
+/// Vault passphrase from `RCLI_VAULT_PASSPHRASE`, or an interactive prompt
+pub fn get_vault_passphrase(confirm: bool) -> anyhow::Result<String> {
+    prompt_passphrase("RCLI_VAULT_PASSPHRASE", "Vault passphrase: ", confirm)
+}
+
+/// Resolve a `vault:name` reference from [`vault_path`], `None` if `value` is not a reference
+pub fn resolve_vault_ref(value: &str) -> anyhow::Result<Option<Vec<u8>>> {
+    let Some(name) = value.strip_prefix(VAULT_REF_PREFIX) else {
+        return Ok(None);
+    };
+    let passphrase = get_vault_passphrase(false)?;
+    process_vault_get(&vault_path(), &passphrase, name).map(Some)
+}
+
+pub fn process_vault_init(path: &Path, passphrase: &str) -> anyhow::Result<()> {
+    if path.exists() {
+        anyhow::bail!("vault {} already exists", path.display());
+    }
+    let vault = Vault {
+        version: VAULT_VERSION,
+        ..Default::default()
+    };
+    vault.save(path, passphrase)
+}
+
+pub fn process_vault_set(
+    path: &Path,
+    passphrase: &str,
+    name: &str,
+    value: &[u8],
+) -> anyhow::Result<()> {
+    if name.is_empty() {
+        anyhow::bail!("secret name can't be empty");
+    }
+    let mut vault = Vault::load(path, passphrase)?;
+    vault
+        .secrets
+        .insert(name.to_string(), STANDARD.encode(value));
+    vault.save(path, passphrase)
+}
+
+pub fn process_vault_get(path: &Path, passphrase: &str, name: &str) -> anyhow::Result<Vec<u8>> {
+    Vault::load(path, passphrase)?.get(name)
+}
+
+pub fn process_vault_list(path: &Path, passphrase: &str) -> anyhow::Result<Vec<String>> {
+    let vault = Vault::load(path, passphrase)?;
+    Ok(vault.secrets.into_keys().collect())
+}
+
+pub fn process_vault_remove(path: &Path, passphrase: &str, name: &str) -> anyhow::Result<()> {
+    let mut vault = Vault::load(path, passphrase)?;
+    if vault.secrets.remove(name).is_none() {
+        anyhow::bail!("secret {} not found in vault", name);
+    }
+    vault.save(path, passphrase)
+}
+
+/// Re-encrypt the vault under a new passphrase
+pub fn process_vault_rotate(
+    path: &Path,
+    passphrase: &str,
+    new_passphrase: &str,
+) -> anyhow::Result<()> {
+    let vault = Vault::load(path, passphrase)?;
+    vault.save(path, new_passphrase)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vault_lifecycle() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("test.vault");
+        process_vault_init(&path, "pass")?;
+        assert!(process_vault_init(&path, "pass").is_err());
+
+        process_vault_set(&path, "pass", "jwt", b"thisisasecret")?;
+        process_vault_set(&path, "pass", "key", &[0, 159, 146, 150])?;
+        assert_eq!(process_vault_list(&path, "pass")?, vec!["jwt", "key"]);
+        assert_eq!(
+            process_vault_get(&path, "pass", "key")?,
+            vec![0, 159, 146, 150]
+        );
+        assert!(process_vault_get(&path, "wrong", "jwt").is_err());
+
+        process_vault_rotate(&path, "pass", "new pass")?;
+        assert!(process_vault_list(&path, "pass").is_err());
+        assert_eq!(
+            process_vault_get(&path, "new pass", "jwt")?,
+            b"thisisasecret"
+        );
+
+        process_vault_remove(&path, "new pass", "jwt")?;
+        assert!(process_vault_get(&path, "new pass", "jwt").is_err());
+        assert!(process_vault_remove(&path, "new pass", "jwt").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,40 @@
+use serde::Serialize;
+
+/// Outcome of a signature or token check, printed as JSON with `--json`
+#[derive(Debug, Serialize)]
+pub struct VerifyResult {
+    pub valid: bool,
+    pub algorithm: String,
+    pub key_id: Option<String>,
+    pub reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub claims: Option<serde_json::Value>,
+}
+
+impl VerifyResult {
+    pub fn new(valid: bool, algorithm: impl Into<String>) -> Self {
+        let reason = match valid {
+            true => None,
+            false => Some("signature mismatch".to_string()),
+        };
+        Self {
+            valid,
+            algorithm: algorithm.into(),
+            key_id: None,
+            reason,
+            claims: None,
+        }
+    }
+
+    pub fn invalid(algorithm: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self {
+            reason: Some(reason.into()),
+            ..Self::new(false, algorithm)
+        }
+    }
+
+    pub fn with_key_id(mut self, key_id: Option<String>) -> Self {
+        self.key_id = key_id;
+        self
+    }
+}
@@ -1,5 +1,6 @@
-use std::fs::File;
-use std::io::Read;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Read, Write};
+use std::path::Path;
 
 pub fn get_reader(input: &str) -> anyhow::Result<Box<dyn Read>> {
     let reader: Box<dyn Read> = if input == "-" {
@@ -9,3 +10,110 @@ pub fn get_reader(input: &str) -> anyhow::Result<Box<dyn Read>> {
     };
     Ok(reader)
 }
+
+/// Write to the output file, or stdout when there is none
+pub fn get_writer(output: Option<&Path>) -> anyhow::Result<Box<dyn Write>> {
+    let writer: Box<dyn Write> = match output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(BufWriter::new(std::io::stdout().lock())),
+    };
+    Ok(writer)
+}
+
+/// Fill `buf` from the reader, it's only short when the reader runs out
+pub fn read_full(reader: &mut dyn Read, buf: &mut [u8]) -> anyhow::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        let n = reader.read(&mut buf[filled..])?;
+        if n == 0 {
+            break;
+        }
+        filled += n;
+    }
+    Ok(filled)
+}
+
+/// Read the whole input without the single trailing newline `echo` and most commands add
+pub fn read_line_input(input: &str) -> anyhow::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    get_reader(input)?.read_to_end(&mut data)?;
+    if data.ends_with(b"\n") {
+        data.pop();
+        if data.ends_with(b"\r") {
+            data.pop();
+        }
+    }
+    Ok(data)
+}
+
+/// Escape `\`, newline and carriage return in a checksum or manifest path, like sha256sum.
+/// `None` means nothing needed escaping; otherwise the line gets a leading `\`.
+pub fn escape_path(path: &str) -> Option<String> {
+    if !path.contains(['\\', '\n', '\r']) {
+        return None;
+    }
+    Some(
+        path.replace('\\', "\\\\")
+            .replace('\n', "\\n")
+            .replace('\r', "\\r"),
+    )
+}
+
+/// Undo [`escape_path`] for a line that started with `\`
+pub fn unescape_path(path: &str) -> anyhow::Result<String> {
+    let mut unescaped = String::with_capacity(path.len());
+    let mut chars = path.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('\\') => unescaped.push('\\'),
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            _ => anyhow::bail!("invalid escape in path: {}", path),
+        }
+    }
+    Ok(unescaped)
+}
+
+/// Get the key passphrase from `RCLI_KEY_PASSPHRASE`, or prompt for it on the terminal
+pub fn get_passphrase(confirm: bool) -> anyhow::Result<String> {
+    prompt_passphrase("RCLI_KEY_PASSPHRASE", "Key passphrase: ", confirm)
+}
+
+/// Get a passphrase from the `env` variable, or prompt for it on the terminal
+pub fn prompt_passphrase(env: &str, prompt: &str, confirm: bool) -> anyhow::Result<String> {
+    if let Ok(passphrase) = std::env::var(env) {
+        return Ok(passphrase);
+    }
+    let passphrase = rpassword::prompt_password(prompt)?;
+    if passphrase.is_empty() {
+        anyhow::bail!("passphrase can't be empty");
+    }
+    if confirm && rpassword::prompt_password("Confirm passphrase: ")? != passphrase {
+        anyhow::bail!("passphrases don't match");
+    }
+    Ok(passphrase)
+}
+
+/// Write a secret to a file that is only readable by the current user
+pub fn write_secret_file(path: impl AsRef<Path>, content: &[u8]) -> anyhow::Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&path)?;
+    // mode only applies to new files, tighten existing ones as well
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    }
+    file.write_all(content)?;
+    Ok(())
+}